@@ -1,100 +1,5622 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 
+/// Read-only queries other contracts can run against a todo list
+#[ink::trait_definition]
+pub trait TaskQuery {
+    /// Number of tasks of a user
+    #[ink(message)]
+    fn task_count(&self, user: ink::primitives::AccountId) -> u32;
+}
+
 #[ink::contract]
 mod tasks {
+    use super::TaskQuery;
+    use ink::env::call::{build_call, ExecutionInput, Selector};
     use ink::prelude::string::String;
     use ink::prelude::vec::Vec;
     use ink::storage::Mapping;
+    use scale::Encode;
+
+    /// Number of tasks in each state, as `(todo, wip, done)`
+    pub type StateCounts = (u32, u32, u32);
+
+    /// Number of open tasks at a block timestamp, as `(timestamp, open)`
+    pub type BurndownPoint = (u64, u32);
+
+    /// Length of a day in block timestamp units (milliseconds)
+    const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+    /// Number of points kept in the burndown history of a user
+    const MAX_BURNDOWN_POINTS: usize = 30;
+
+    /// Number of tags a single task can carry
+    const MAX_TAGS_PER_TASK: usize = 8;
+
+    /// Maximum length of a task description, in bytes
+    const MAX_DESCRIPTION_LEN: usize = 256;
+
+    /// `MutationApplied` op code of a write that grew a list
+    const OP_ADD: u8 = 0;
+    /// `MutationApplied` op code of a write that shrank a list
+    const OP_REMOVE: u8 = 1;
+    /// `MutationApplied` op code of a write that kept the size of a list
+    const OP_UPDATE: u8 = 2;
+
+    /// Represents the state of a task
+    #[derive(scale::Decode, scale::Encode, Clone, Copy, Debug, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum TaskState {
+        Todo,
+        Wip,
+        Done,
+    }
+
+    impl TaskState {
+        /// Numeric representation of the state
+        pub fn as_u8(&self) -> u8 {
+            match self {
+                TaskState::Todo => 0,
+                TaskState::Wip => 1,
+                TaskState::Done => 2,
+            }
+        }
+
+        /// Display name of the state
+        pub fn name(&self) -> &'static str {
+            match self {
+                TaskState::Todo => "Todo",
+                TaskState::Wip => "Wip",
+                TaskState::Done => "Done",
+            }
+        }
+
+        /// Parses a state from its numeric representation
+        pub fn from_u8(v: u8) -> Option<TaskState> {
+            match v {
+                0 => Some(TaskState::Todo),
+                1 => Some(TaskState::Wip),
+                2 => Some(TaskState::Done),
+                _ => None,
+            }
+        }
+    }
+
+    /// How important a task is
+    #[derive(scale::Decode, scale::Encode, Clone, Copy, Debug, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum Priority {
+        Low,
+        Medium,
+        High,
+    }
+
+    impl Priority {
+        /// Weight of the priority in progress scores
+        pub fn weight(&self) -> u32 {
+            match self {
+                Priority::Low => 1,
+                Priority::Medium => 2,
+                Priority::High => 3,
+            }
+        }
+    }
+
+    /// A single task
+    #[derive(scale::Decode, scale::Encode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Task {
+        /// Stable identifier assigned when the task is added
+        id: u64,
+        title: String,
+        description: String,
+        state: TaskState,
+        /// Block timestamp at which the task was added
+        created_at: u64,
+        /// Block timestamp after which the task is overdue
+        due: Option<u64>,
+        /// Whether a `TaskOverdue` event was already emitted for the task
+        overdue_flagged: bool,
+        /// Free-form labels attached to the task
+        tags: Vec<String>,
+        /// Block timestamp at which the task was last done
+        completed_at: Option<u64>,
+        priority: Priority,
+        /// Estimated effort needed to get the task done
+        effort_minutes: u32,
+        /// Block timestamp of the last change to the task
+        updated_at: u64,
+        /// Checklist items of the task
+        subtasks: Vec<String>,
+        /// Account the task is assigned to, if any
+        assignee: Option<AccountId>,
+    }
+
+    impl Task {
+        /// A task with the given title, description and state, every other
+        /// field being left to its default
+        pub fn new(title: String, description: String, state: TaskState) -> Task {
+            Task {
+                id: 0,
+                title,
+                description,
+                state,
+                created_at: 0,
+                due: None,
+                overdue_flagged: false,
+                tags: Vec::new(),
+                completed_at: None,
+                priority: Priority::Medium,
+                effort_minutes: 0,
+                updated_at: 0,
+                subtasks: Vec::new(),
+                assignee: None,
+            }
+        }
+
+        /// Whether the task is still open past its due date
+        fn is_overdue(&self, now: u64) -> bool {
+            self.state != TaskState::Done && self.due.is_some_and(|due| due < now)
+        }
+    }
+
+    /// Limits applying to a single user, `None` falling back to the
+    /// contract-wide value
+    #[derive(scale::Decode, scale::Encode, Clone, Copy, Debug, Default, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct UserSettings {
+        /// Overrides `max_wip`
+        pub wip_limit: Option<u32>,
+        /// Overrides `soft_task_limit`
+        pub soft_task_limit: Option<u32>,
+        /// Overrides `max_bytes_per_user`
+        pub max_bytes: Option<u32>,
+    }
+
+    /// Emitted when a task is added to a list
+    #[ink(event)]
+    pub struct TaskAdded {
+        #[ink(topic)]
+        user: AccountId,
+        title: String,
+    }
+
+    /// Emitted instead of `TaskAdded` events when a batch of tasks is added
+    /// with `aggregate_batch_events` on
+    #[ink(event)]
+    pub struct BulkAdded {
+        #[ink(topic)]
+        user: AccountId,
+        count: u32,
+    }
+
+    /// Emitted when a task passes its due date without being done
+    #[ink(event)]
+    pub struct TaskOverdue {
+        #[ink(topic)]
+        user: AccountId,
+        title: String,
+    }
+
+    /// Emitted when a task is handed over to another user
+    #[ink(event)]
+    pub struct TaskDelegated {
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        title: String,
+    }
+
+    /// Snapshot of the state counts of a list
+    #[ink(event)]
+    pub struct ListSummary {
+        #[ink(topic)]
+        user: AccountId,
+        todo: u32,
+        wip: u32,
+        done: u32,
+    }
+
+    /// Emitted once per list written by a message, so that indexers can follow
+    /// lists from events alone; a move between two lists reports both
+    #[ink(event)]
+    pub struct MutationApplied {
+        #[ink(topic)]
+        user: AccountId,
+        /// One of `OP_ADD`, `OP_REMOVE` or `OP_UPDATE`
+        op: u8,
+        /// Number of tasks in the list after the write
+        new_count: u32,
+    }
+
+    /// Errors that can occur upon calling this contract
+    #[derive(scale::Decode, scale::Encode, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    #[allow(clippy::enum_variant_names)]
+    pub enum Error {
+        /// The same title appears more than once in a list
+        DuplicateTitle,
+        /// No task with the given title exists
+        TaskNotFound,
+        /// The caller is not the owner of the contract
+        NotOwner,
+        /// The list was modified after the snapshot was taken
+        StaleSnapshot,
+        /// The task does not carry the given tag
+        TagNotFound,
+        /// Too many tasks were added to the list within the current block
+        RateLimited,
+        /// The caller is neither the owner nor a manager
+        NotAuthorized,
+        /// The list of the user is frozen
+        UserFrozen,
+        /// The description of the task is not allowed
+        InvalidDescription,
+        /// The line at the given index could not be parsed
+        ParseError(u32),
+        /// There is no previous list to go back to
+        NothingToUndo,
+        /// The title contains control characters
+        InvalidTitle,
+        /// The list would grow past the byte quota
+        QuotaExceeded,
+        /// The list already holds as many Wip tasks as allowed
+        WipLimitReached,
+        /// The titles are not a permutation of the titles of the list
+        InvalidOrdering,
+        /// The user never had a task list
+        UserNotFound,
+        /// The contract is paused for an upgrade
+        Paused,
+        /// The task already carries as many tags as allowed
+        TagLimitReached,
+        /// The description is longer than `MAX_DESCRIPTION_LEN`
+        DescriptionTooLong,
+        /// The same id appears more than once in a list, or was never handed out
+        InvalidId,
+    }
+
+    /// Task storage
+    #[ink(storage)]
+    pub struct Tasks {
+        tasks: Mapping<AccountId, Vec<Task>>,
+        /// State given to tasks created through `add_task_titled`
+        default_state: TaskState,
+        /// Account allowed to run administrative messages
+        owner: AccountId,
+        /// Every account that ever got a task list, by registration order
+        users: Mapping<u32, AccountId>,
+        /// Number of entries of `users`
+        user_count: u32,
+        /// Position of each registered account in `users`
+        user_index: Mapping<AccountId, u32>,
+        /// Contract whose `reward(user)` message is called when a task gets done
+        rewards_contract: Option<AccountId>,
+        /// Owners who shared their list, per viewer
+        shared_with: Mapping<AccountId, Vec<AccountId>>,
+        /// Block number of the last write to each user's list
+        last_modified_block: Mapping<AccountId, u64>,
+        /// Maximum number of tasks added to a list per block, 0 meaning unlimited
+        max_adds_per_block: u32,
+        /// Block number and number of tasks added within it, per user
+        adds_in_block: Mapping<AccountId, (BlockNumber, u32)>,
+        /// Identifier given to the next added task
+        next_id: u64,
+        /// Number of consecutive days with at least one task done, per user
+        streak: Mapping<AccountId, u32>,
+        /// Last day (block timestamp / `DAY_MS`) a user got a task done
+        last_completion_day: Mapping<AccountId, u64>,
+        /// Accounts allowed to moderate lists alongside the owner
+        managers: Mapping<AccountId, ()>,
+        /// Users whose list cannot be modified
+        frozen: Mapping<AccountId, ()>,
+        /// Whether tasks can be added without a description
+        allow_empty_description: bool,
+        /// Cached `(todo, wip, done)` counts of each list, refreshed on every write
+        state_counts: Mapping<AccountId, StateCounts>,
+        /// Whether batch adds emit a single `BulkAdded` event
+        aggregate_batch_events: bool,
+        /// List of each user as it was before the last write
+        last_snapshot: Mapping<AccountId, Vec<Task>>,
+        /// Maximum encoded size of a list, 0 meaning unlimited
+        max_bytes_per_user: u32,
+        /// Tasks moved out of the list of each user
+        archived: Mapping<AccountId, Vec<Task>>,
+        /// Maximum number of Wip tasks in a list, 0 meaning unlimited
+        max_wip: u32,
+        /// Per-user overrides of the contract-wide limits
+        settings: Mapping<AccountId, UserSettings>,
+        /// Contract notified of the overdue tasks of each user
+        reminders: Mapping<AccountId, AccountId>,
+        /// `(timestamp, open tasks)` points recorded for each user
+        burndown: Mapping<AccountId, Vec<BurndownPoint>>,
+        /// Users whose list anyone can read and use as a template
+        public: Mapping<AccountId, ()>,
+        /// Whether lists are read-only for an upgrade
+        paused: bool,
+        /// Task count past which `add_task_checked` warns, 0 meaning never
+        soft_task_limit: u32,
+        /// Number of tasks ever added to the list of each user
+        added_total: Mapping<AccountId, u64>,
+        /// Number of tasks ever removed from the list of each user
+        removed_total: Mapping<AccountId, u64>,
+        /// Whether descriptions past `MAX_DESCRIPTION_LEN` are cut instead of rejected
+        truncate_long_descriptions: bool,
+        /// Whether the contract emits events at all
+        emit_events: bool,
+    }
+
+    impl Tasks {
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        #[ink(constructor)]
+        pub fn default() -> Self {
+            Self::with_default_state(TaskState::Todo)
+        }
+
+        #[ink(constructor)]
+        pub fn with_default_state(default_state: TaskState) -> Self {
+            Self {
+                tasks: Mapping::new(),
+                default_state,
+                owner: Self::env().caller(),
+                users: Mapping::new(),
+                user_count: 0,
+                user_index: Mapping::new(),
+                rewards_contract: None,
+                shared_with: Mapping::new(),
+                last_modified_block: Mapping::new(),
+                max_adds_per_block: 0,
+                adds_in_block: Mapping::new(),
+                next_id: 0,
+                streak: Mapping::new(),
+                last_completion_day: Mapping::new(),
+                managers: Mapping::new(),
+                frozen: Mapping::new(),
+                allow_empty_description: true,
+                state_counts: Mapping::new(),
+                aggregate_batch_events: false,
+                last_snapshot: Mapping::new(),
+                max_bytes_per_user: 0,
+                archived: Mapping::new(),
+                max_wip: 0,
+                settings: Mapping::new(),
+                reminders: Mapping::new(),
+                burndown: Mapping::new(),
+                public: Mapping::new(),
+                paused: false,
+                soft_task_limit: 0,
+                added_total: Mapping::new(),
+                removed_total: Mapping::new(),
+                truncate_long_descriptions: false,
+                emit_events: true,
+            }
+        }
+
+        #[ink(constructor)]
+        pub fn with_batch_events(aggregate_batch_events: bool) -> Self {
+            let mut contract = Self::default();
+            contract.aggregate_batch_events = aggregate_batch_events;
+            contract
+        }
+
+        #[ink(constructor)]
+        pub fn with_events(emit_events: bool) -> Self {
+            let mut contract = Self::default();
+            contract.emit_events = emit_events;
+            contract
+        }
+
+        #[ink(constructor)]
+        pub fn with_truncated_descriptions(truncate_long_descriptions: bool) -> Self {
+            let mut contract = Self::default();
+            contract.truncate_long_descriptions = truncate_long_descriptions;
+            contract
+        }
+
+        #[ink(constructor)]
+        pub fn with_description_policy(allow_empty_description: bool) -> Self {
+            let mut contract = Self::default();
+            contract.allow_empty_description = allow_empty_description;
+            contract
+        }
+
+        #[ink(constructor)]
+        pub fn for_team(owner: AccountId, members: Vec<AccountId>) -> Self {
+            let mut contract = Self::default();
+            contract.owner = owner;
+            for member in members {
+                if !contract.tasks.contains(member) {
+                    contract.write_tasks(member, &Vec::new());
+                }
+            }
+            contract
+        }
+
+        /// Add a task to the storage
+        #[ink(message)]
+        pub fn add_task(&mut self, user: AccountId, task: Task) -> Result<(), Error> {
+            let title = task.title.clone();
+            self.push_task(user, task)?;
+            self.emit_mutation(user, OP_ADD);
+            self.emit(TaskAdded { user, title });
+            Ok(())
+        }
+
+        /// Add a task built from a title and a description, in the default state
+        #[ink(message)]
+        pub fn add_task_titled(
+            &mut self,
+            user: AccountId,
+            title: String,
+            description: String,
+        ) -> Result<(), Error> {
+            let task = Task::new(title, description, self.default_state);
+            self.add_task(user, task)
+        }
+
+        /// Remove a task from the storage
+        #[ink(message)]
+        pub fn remove_task(&mut self, user: AccountId, task_title: String) -> Result<(), Error> {
+            self.ensure_writable(user)?;
+
+            let mut tasks = self.get_task(user);
+            let position = tasks
+                .iter()
+                .position(|t| t.title == task_title)
+                .ok_or(Error::TaskNotFound)?;
+            tasks.remove(position);
+            self.store(user, &tasks)?;
+            self.record_removals(user, 1);
+            Ok(())
+        }
+
+        /// Fetchs the task of a single user
+        #[ink(message)]
+        pub fn get_task(&self, user: AccountId) -> Vec<Task> {
+            if !self.tasks.contains(user) {
+                let empty_tasks: Vec<Task> = Vec::new();
+                empty_tasks
+            } else {
+                self.tasks.get(user).unwrap()
+            }
+        }
+
+        /// Overwrites the whole task list of a user, giving every task a new id
+        #[ink(message)]
+        pub fn replace_all_tasks(
+            &mut self,
+            user: AccountId,
+            tasks: Vec<Task>,
+        ) -> Result<(), Error> {
+            self.ensure_writable(user)?;
+            self.validate_list(user, &tasks)?;
+
+            let mut tasks = tasks;
+            for task in tasks.iter_mut() {
+                task.id = self.next_id;
+                self.next_id += 1;
+                self.stamp_completion(task);
+            }
+
+            let replaced = self.get_task(user).len() as u64;
+            self.store(user, &tasks)?;
+            self.record_removals(user, replaced);
+            self.record_adds(user, tasks.len() as u64);
+            Ok(())
+        }
+
+        /// Fetches the tasks of a user split into `(todo, wip, done)`
+        #[ink(message)]
+        pub fn grouped_by_state(&self, user: AccountId) -> (Vec<Task>, Vec<Task>, Vec<Task>) {
+            let mut todo = Vec::new();
+            let mut wip = Vec::new();
+            let mut done = Vec::new();
+
+            for task in self.get_task(user) {
+                match task.state {
+                    TaskState::Todo => todo.push(task),
+                    TaskState::Wip => wip.push(task),
+                    TaskState::Done => done.push(task),
+                }
+            }
+
+            (todo, wip, done)
+        }
+
+        /// Counts the tasks of a user created within `[start, end]`
+        #[ink(message)]
+        pub fn tasks_created_between(&self, user: AccountId, start: u64, end: u64) -> u32 {
+            self.get_task(user)
+                .iter()
+                .filter(|t| t.created_at >= start && t.created_at <= end)
+                .count() as u32
+        }
+
+        /// Fetches the most recently added task of a user
+        #[ink(message)]
+        pub fn latest_task(&self, user: AccountId) -> Option<Task> {
+            self.get_task(user).into_iter().max_by_key(|t| t.created_at)
+        }
+
+        /// Emits a `TaskOverdue` event for every open task of a user that went
+        /// past its due date since the last call, returning how many were flagged
+        #[ink(message)]
+        pub fn flag_overdue(&mut self, user: AccountId) -> Result<u32, Error> {
+            self.ensure_writable(user)?;
+
+            let now = self.env().block_timestamp();
+            let mut tasks = self.get_task(user);
+            let mut flagged = 0;
+
+            for task in tasks.iter_mut() {
+                if task.is_overdue(now) && !task.overdue_flagged {
+                    task.overdue_flagged = true;
+                    flagged += 1;
+                    self.emit(TaskOverdue {
+                        user,
+                        title: task.title.clone(),
+                    });
+                }
+            }
+
+            if flagged > 0 {
+                self.store(user, &tasks)?;
+            }
+            Ok(flagged)
+        }
+
+        /// Moves one of the caller's tasks to the list of another user, which
+        /// cannot be undone
+        #[ink(message)]
+        pub fn delegate_task(&mut self, title: String, to: AccountId) -> Result<(), Error> {
+            let from = self.env().caller();
+            self.ensure_writable(from)?;
+            self.ensure_writable(to)?;
+
+            let mut from_tasks = self.get_task(from);
+            let mut to_tasks = self.get_task(to);
+
+            let position = from_tasks
+                .iter()
+                .position(|t| t.title == title)
+                .ok_or(Error::TaskNotFound)?;
+            if to_tasks.iter().any(|t| t.title == title) {
+                return Err(Error::DuplicateTitle);
+            }
+
+            let moved = from_tasks.remove(position);
+            if moved.state == TaskState::Wip {
+                self.ensure_wip_capacity(to, Self::count_states(&to_tasks).1)?;
+            }
+            to_tasks.push(moved);
+            self.ensure_quota(to, &to_tasks)?;
+            self.write_tasks(from, &from_tasks);
+            self.write_tasks(to, &to_tasks);
+            self.last_snapshot.remove(from);
+            self.last_snapshot.remove(to);
+            self.record_removals(from, 1);
+            self.record_adds(to, 1);
+            self.emit_mutation(from, OP_REMOVE);
+            self.emit_mutation(to, OP_ADD);
+            self.emit(TaskDelegated { from, to, title });
+            Ok(())
+        }
+
+        /// Returns the `(user, todo, wip, done)` counts of each given user
+        #[ink(message)]
+        pub fn state_breakdown(
+            &self,
+            users: Vec<AccountId>,
+        ) -> Result<Vec<(AccountId, u32, u32, u32)>, Error> {
+            self.ensure_owner()?;
+
+            Ok(users
+                .into_iter()
+                .map(|user| {
+                    let (todo, wip, done) = Self::count_states(&self.get_task(user));
+                    (user, todo, wip, done)
+                })
+                .collect())
+        }
+
+        /// Updates the description and/or the state of a task in a single write
+        #[ink(message)]
+        pub fn update_task(
+            &mut self,
+            user: AccountId,
+            title: String,
+            new_description: Option<String>,
+            new_state: Option<TaskState>,
+        ) -> Result<(), Error> {
+            self.ensure_writable(user)?;
+
+            let mut tasks = self.get_task(user);
+            let (_, wip, _) = Self::count_states(&tasks);
+            let task = Self::find_task(&mut tasks, &title)?;
+
+            if let Some(mut description) = new_description {
+                if !self.allow_empty_description && description.is_empty() {
+                    return Err(Error::InvalidDescription);
+                }
+                self.fit_description(&mut description)?;
+                task.description = description;
+                task.updated_at = self.env().block_timestamp();
+            }
+            if let Some(state) = new_state {
+                if state == TaskState::Wip && task.state != TaskState::Wip {
+                    self.ensure_wip_capacity(user, wip)?;
+                }
+                self.set_state(user, task, state);
+            }
+
+            self.store(user, &tasks)?;
+            Ok(())
+        }
+
+        /// Fetches every account that has a task list
+        #[ink(message)]
+        pub fn get_all_users(&self) -> Vec<AccountId> {
+            self.all_users()
+        }
+
+        /// Fetches the titles that appear more than once in the list of a user
+        #[ink(message)]
+        pub fn find_duplicate_titles(&self, user: AccountId) -> Vec<String> {
+            let tasks = self.get_task(user);
+            let mut duplicates: Vec<String> = Vec::new();
+
+            for (i, task) in tasks.iter().enumerate() {
+                if tasks[..i].iter().any(|t| t.title == task.title)
+                    && !duplicates.contains(&task.title)
+                {
+                    duplicates.push(task.title.clone());
+                }
+            }
+
+            duplicates
+        }
+
+        /// Sets the contract notified when a task gets done
+        #[ink(message)]
+        pub fn set_rewards_contract(&mut self, contract: Option<AccountId>) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.rewards_contract = contract;
+            Ok(())
+        }
+
+        /// Whether a user has at least `n` tasks done
+        #[ink(message)]
+        pub fn has_completed_at_least(&self, user: AccountId, n: u32) -> bool {
+            let (_, _, done) = Self::count_states(&self.get_task(user));
+            done >= n
+        }
+
+        /// Shares or unshares the caller's list with a viewer
+        #[ink(message)]
+        pub fn share_list(&mut self, viewer: AccountId, shared: bool) {
+            let owner = self.env().caller();
+            let mut owners = self.shared_with.get(viewer).unwrap_or_default();
+            let position = owners.iter().position(|o| *o == owner);
+
+            match (shared, position) {
+                (true, None) => owners.push(owner),
+                (false, Some(position)) => {
+                    owners.remove(position);
+                }
+                _ => return,
+            }
+            self.shared_with.insert(viewer, &owners);
+        }
+
+        /// Fetches the caller's tasks along with the tasks shared with them,
+        /// tagged by owner
+        #[ink(message)]
+        pub fn visible_tasks(&self) -> Vec<(AccountId, Task)> {
+            let caller = self.env().caller();
+            let mut owners = Vec::from([caller]);
+            owners.extend(self.shared_with.get(caller).unwrap_or_default());
+
+            owners
+                .into_iter()
+                .flat_map(|owner| {
+                    self.get_task(owner)
+                        .into_iter()
+                        .map(move |task| (owner, task))
+                })
+                .collect()
+        }
+
+        /// Fetches the tasks of a user along with the current block number
+        #[ink(message)]
+        pub fn snapshot(&self, user: AccountId) -> (u64, Vec<Task>) {
+            (self.env().block_number() as u64, self.get_task(user))
+        }
+
+        /// Restores a list from a snapshot, unless it was modified after the
+        /// snapshot was taken
+        #[ink(message)]
+        pub fn restore_snapshot(
+            &mut self,
+            user: AccountId,
+            snapshot_block: u64,
+            tasks: Vec<Task>,
+        ) -> Result<(), Error> {
+            self.ensure_writable(user)?;
+
+            if self.last_modified_block.get(user).unwrap_or_default() > snapshot_block {
+                return Err(Error::StaleSnapshot);
+            }
+            self.validate_list(user, &tasks)?;
+            for (i, task) in tasks.iter().enumerate() {
+                if task.id >= self.next_id || tasks[..i].iter().any(|t| t.id == task.id) {
+                    return Err(Error::InvalidId);
+                }
+            }
+
+            self.store(user, &tasks)?;
+            Ok(())
+        }
+
+        /// Counts how many tasks of a user carry each distinct tag
+        #[ink(message)]
+        pub fn tag_histogram(&self, user: AccountId) -> Vec<(String, u32)> {
+            let mut histogram: Vec<(String, u32)> = Vec::new();
+
+            for tag in self.get_task(user).into_iter().flat_map(|t| t.tags) {
+                match histogram.iter_mut().find(|(t, _)| *t == tag) {
+                    Some((_, count)) => *count += 1,
+                    None => histogram.push((tag, 1)),
+                }
+            }
+
+            histogram
+        }
+
+        /// Attaches a tag to a task
+        #[ink(message)]
+        pub fn add_tag(
+            &mut self,
+            user: AccountId,
+            title: String,
+            tag: String,
+        ) -> Result<(), Error> {
+            self.ensure_writable(user)?;
+
+            let mut tasks = self.get_task(user);
+            let task = Self::find_task(&mut tasks, &title)?;
+
+            if !task.tags.contains(&tag) {
+                if task.tags.len() >= MAX_TAGS_PER_TASK {
+                    return Err(Error::TagLimitReached);
+                }
+                task.tags.push(tag);
+                task.updated_at = self.env().block_timestamp();
+                self.store(user, &tasks)?;
+            }
+            Ok(())
+        }
+
+        /// Detaches a tag from a task
+        #[ink(message)]
+        pub fn remove_tag(
+            &mut self,
+            user: AccountId,
+            title: String,
+            tag: String,
+        ) -> Result<(), Error> {
+            self.ensure_writable(user)?;
+
+            let mut tasks = self.get_task(user);
+            let task = Self::find_task(&mut tasks, &title)?;
+            let position = task
+                .tags
+                .iter()
+                .position(|t| *t == tag)
+                .ok_or(Error::TagNotFound)?;
+
+            task.tags.remove(position);
+            task.updated_at = self.env().block_timestamp();
+            self.store(user, &tasks)?;
+            Ok(())
+        }
+
+        /// Emits a `ListSummary` event with the state counts of a user's list
+        ///
+        /// Nothing is returned: it has to be submitted as a transaction for the
+        /// event to be recorded, a dry-run will not emit anything.
+        #[ink(message)]
+        pub fn emit_summary(&self, user: AccountId) {
+            let (todo, wip, done) = Self::count_states(&self.get_task(user));
+            self.emit(ListSummary {
+                user,
+                todo,
+                wip,
+                done,
+            });
+        }
+
+        /// Whether a user has a task with the given title
+        #[ink(message)]
+        pub fn task_exists(&self, user: AccountId, title: String) -> bool {
+            self.get_task(user).iter().any(|t| t.title == title)
+        }
+
+        /// Sets how many tasks can be added to a list within a block, 0 meaning unlimited
+        #[ink(message)]
+        pub fn set_max_adds_per_block(&mut self, max_adds_per_block: u32) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.max_adds_per_block = max_adds_per_block;
+            Ok(())
+        }
+
+        /// Fetches the tasks of a user paired with their id
+        #[ink(message)]
+        pub fn get_tasks_with_ids(&self, user: AccountId) -> Vec<(u64, Task)> {
+            self.get_task(user).into_iter().map(|t| (t.id, t)).collect()
+        }
+
+        /// Updates the state of several tasks at once, leaving the list untouched
+        /// if any title is missing
+        #[ink(message)]
+        pub fn batch_update_states(
+            &mut self,
+            user: AccountId,
+            updates: Vec<(String, TaskState)>,
+        ) -> Result<(), Error> {
+            self.ensure_writable(user)?;
+
+            let mut tasks = self.get_task(user);
+            if !updates
+                .iter()
+                .all(|(title, _)| tasks.iter().any(|t| t.title == *title))
+            {
+                return Err(Error::TaskNotFound);
+            }
+
+            let mut states: Vec<TaskState> = tasks.iter().map(|t| t.state).collect();
+            for (title, state) in &updates {
+                if let Some(i) = tasks.iter().position(|t| t.title == *title) {
+                    states[i] = *state;
+                }
+            }
+            let (_, wip, _) = Self::count_states(&tasks);
+            let new_wip = states.iter().filter(|s| **s == TaskState::Wip).count() as u32;
+            if new_wip > wip {
+                self.ensure_wip_capacity(user, new_wip - 1)?;
+            }
+
+            for (title, state) in updates {
+                let task = Self::find_task(&mut tasks, &title)?;
+                self.set_state(user, task, state);
+            }
+
+            self.store(user, &tasks)?;
+            Ok(())
+        }
+
+        /// Fetches the tasks of a user sorted by title
+        #[ink(message)]
+        pub fn get_tasks_sorted_by_title(&self, user: AccountId, ascending: bool) -> Vec<Task> {
+            let mut tasks = self.get_task(user);
+            if ascending {
+                tasks.sort_by(|a, b| a.title.cmp(&b.title));
+            } else {
+                tasks.sort_by(|a, b| b.title.cmp(&a.title));
+            }
+            tasks
+        }
+
+        /// Number of consecutive days, up to today or yesterday, on which a user
+        /// got at least one task done
+        #[ink(message)]
+        pub fn get_streak(&self, user: AccountId) -> u32 {
+            let today = self.env().block_timestamp() / DAY_MS;
+            match self.last_completion_day.get(user) {
+                Some(day) if day + 1 >= today => self.streak.get(user).unwrap_or_default(),
+                _ => 0,
+            }
+        }
+
+        /// Whether the list of a user was written after the given block
+        #[ink(message)]
+        pub fn changed_since(&self, user: AccountId, block: u64) -> bool {
+            self.last_modified_block.get(user).unwrap_or_default() > block
+        }
+
+        /// Grants or revokes the manager role
+        #[ink(message)]
+        pub fn set_manager(&mut self, account: AccountId, is_manager: bool) -> Result<(), Error> {
+            self.ensure_owner()?;
+            if is_manager {
+                self.managers.insert(account, &());
+            } else {
+                self.managers.remove(account);
+            }
+            Ok(())
+        }
+
+        /// Freezes or unfreezes the list of a user, frozen lists being read-only
+        #[ink(message)]
+        pub fn freeze_user(&mut self, user: AccountId, frozen: bool) -> Result<(), Error> {
+            self.ensure_owner_or_manager()?;
+            if frozen {
+                self.frozen.insert(user, &());
+            } else {
+                self.frozen.remove(user);
+            }
+            Ok(())
+        }
+
+        /// Fetches the titles of the done tasks of a user with the timestamp they
+        /// were completed at, oldest first
+        #[ink(message)]
+        pub fn done_timeline(&self, user: AccountId) -> Vec<(String, u64)> {
+            let mut timeline: Vec<(String, u64)> = self
+                .get_task(user)
+                .into_iter()
+                .filter(|t| t.state == TaskState::Done)
+                .filter_map(|t| t.completed_at.map(|completed_at| (t.title, completed_at)))
+                .collect();
+            timeline.sort_by_key(|(_, completed_at)| *completed_at);
+            timeline
+        }
+
+        /// Fetches a page of the tasks of a user that are in the given state
+        #[ink(message)]
+        pub fn get_state_paged(
+            &self,
+            user: AccountId,
+            state: TaskState,
+            offset: u32,
+            limit: u32,
+        ) -> Vec<Task> {
+            self.get_task(user)
+                .into_iter()
+                .filter(|t| t.state == state)
+                .skip(offset as usize)
+                .take(limit as usize)
+                .collect()
+        }
+
+        /// Terminates the contract, sending its remaining balance to the owner
+        #[ink(message)]
+        pub fn terminate(&mut self) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.env().terminate_contract(self.owner)
+        }
+
+        /// Percentage of the open tasks of a user that are overdue
+        #[ink(message)]
+        pub fn overdue_ratio(&self, user: AccountId) -> u8 {
+            let now = self.env().block_timestamp();
+            let open: Vec<Task> = self
+                .get_task(user)
+                .into_iter()
+                .filter(|t| t.state != TaskState::Done)
+                .collect();
+            if open.is_empty() {
+                return 0;
+            }
+
+            let overdue = open.iter().filter(|t| t.is_overdue(now)).count();
+            (overdue * 100 / open.len()) as u8
+        }
+
+        /// Adds tasks parsed from `title|state|description` lines, `|` being
+        /// the given separator, returning how many were imported
+        #[ink(message)]
+        pub fn import_from_text(
+            &mut self,
+            user: AccountId,
+            lines: Vec<String>,
+            separator: String,
+        ) -> Result<u32, Error> {
+            let mut parsed = Vec::new();
+            for (index, line) in lines.iter().enumerate() {
+                let task = Self::parse_task_line(line, &separator)
+                    .ok_or(Error::ParseError(index as u32))?;
+                parsed.push(task);
+            }
+
+            self.add_batch(user, parsed)
+        }
+
+        /// Fetches the tasks of a user that are not done yet
+        #[ink(message)]
+        pub fn get_open_tasks(&self, user: AccountId) -> Vec<Task> {
+            self.get_task(user)
+                .into_iter()
+                .filter(|t| t.state != TaskState::Done)
+                .collect()
+        }
+
+        /// Updates the state of one of the caller's tasks
+        #[ink(message)]
+        pub fn update_my_task_state(
+            &mut self,
+            title: String,
+            new_state: TaskState,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.update_task(caller, title, None, Some(new_state))
+        }
+
+        /// Returns the `(todo, wip, done)` counts of a user from the cache
+        #[ink(message)]
+        pub fn count_by_state(&self, user: AccountId) -> (u32, u32, u32) {
+            self.state_counts.get(user).unwrap_or_default()
+        }
+
+        /// Returns the `(todo, wip, done)` counts of a user by scanning their list
+        #[ink(message)]
+        pub fn count_by_state_scan(&self, user: AccountId) -> (u32, u32, u32) {
+            Self::count_states(&self.get_task(user))
+        }
+
+        /// Attaches a tag to each named task, returning how many were newly tagged
+        #[ink(message)]
+        pub fn tag_tasks(
+            &mut self,
+            user: AccountId,
+            titles: Vec<String>,
+            tag: String,
+        ) -> Result<u32, Error> {
+            self.ensure_writable(user)?;
+
+            let mut tasks = self.get_task(user);
+            let mut tagged = 0;
+            for title in titles {
+                if let Ok(task) = Self::find_task(&mut tasks, &title) {
+                    if !task.tags.contains(&tag) {
+                        if task.tags.len() >= MAX_TAGS_PER_TASK {
+                            return Err(Error::TagLimitReached);
+                        }
+                        task.tags.push(tag.clone());
+                        task.updated_at = self.env().block_timestamp();
+                        tagged += 1;
+                    }
+                }
+            }
+
+            if tagged > 0 {
+                self.store(user, &tasks)?;
+            }
+            Ok(tagged)
+        }
+
+        /// Adds several tasks to a list, returning how many were added
+        #[ink(message)]
+        pub fn add_tasks(&mut self, user: AccountId, tasks: Vec<Task>) -> Result<u32, Error> {
+            self.add_batch(user, tasks)
+        }
+
+        /// Returns `(task_count, completion_percentage, last_modified_block)` for
+        /// a user
+        #[ink(message)]
+        pub fn list_header(&self, user: AccountId) -> (u32, u8, u64) {
+            let (todo, wip, done) = self.count_by_state(user);
+            let count = todo + wip + done;
+            let completion = (done * 100).checked_div(count).unwrap_or(0);
+            (
+                count,
+                completion as u8,
+                self.last_modified_block.get(user).unwrap_or_default(),
+            )
+        }
+
+        /// Restores the list of a user as it was before the last write; moves
+        /// between two lists and archiving clear the snapshots of the lists
+        /// they touch and cannot be undone
+        #[ink(message)]
+        pub fn undo(&mut self, user: AccountId) -> Result<(), Error> {
+            self.ensure_writable(user)?;
+            let previous = self.last_snapshot.take(user).ok_or(Error::NothingToUndo)?;
+
+            self.store(user, &previous)?;
+            self.last_snapshot.remove(user);
+            Ok(())
+        }
+
+        /// Percentage of the priority weight of a user's tasks that is done
+        #[ink(message)]
+        pub fn weighted_progress(&self, user: AccountId) -> u32 {
+            let tasks = self.get_task(user);
+            let total: u32 = tasks.iter().map(|t| t.priority.weight()).sum();
+            let done: u32 = tasks
+                .iter()
+                .filter(|t| t.state == TaskState::Done)
+                .map(|t| t.priority.weight())
+                .sum();
+
+            (done * 100).checked_div(total).unwrap_or(0)
+        }
+
+        /// Moves the tasks of a user in the given state to the top of the list,
+        /// keeping the relative order of all tasks otherwise
+        #[ink(message)]
+        pub fn float_state_to_top(
+            &mut self,
+            user: AccountId,
+            state: TaskState,
+        ) -> Result<(), Error> {
+            self.ensure_writable(user)?;
+
+            let (mut tasks, rest): (Vec<Task>, Vec<Task>) = self
+                .get_task(user)
+                .into_iter()
+                .partition(|t| t.state == state);
+            tasks.extend(rest);
+            self.store(user, &tasks)?;
+            Ok(())
+        }
+
+        /// Up to `n` done tasks of a user, most recently completed first
+        #[ink(message)]
+        pub fn recent_done(&self, user: AccountId, n: u32) -> Vec<Task> {
+            let mut done: Vec<Task> = self
+                .get_task(user)
+                .into_iter()
+                .filter(|t| t.state == TaskState::Done)
+                .collect();
+            done.sort_by_key(|t| core::cmp::Reverse(t.completed_at));
+            done.truncate(n as usize);
+            done
+        }
+
+        /// Changes the title of a task
+        #[ink(message)]
+        pub fn rename_task(
+            &mut self,
+            user: AccountId,
+            title: String,
+            new_title: String,
+        ) -> Result<(), Error> {
+            self.ensure_writable(user)?;
+            Self::validate_title(&new_title)?;
+
+            let mut tasks = self.get_task(user);
+            if tasks.iter().any(|t| t.title == new_title) {
+                return Err(Error::DuplicateTitle);
+            }
+            let task = Self::find_task(&mut tasks, &title)?;
+            task.title = new_title;
+            task.updated_at = self.env().block_timestamp();
+
+            self.store(user, &tasks)?;
+            Ok(())
+        }
+
+        /// Encoded size in bytes of the list of a user
+        #[ink(message)]
+        pub fn estimated_storage_bytes(&self, user: AccountId) -> u32 {
+            self.get_task(user).encoded_size() as u32
+        }
+
+        /// Sets the maximum encoded size of a list, 0 meaning unlimited
+        #[ink(message)]
+        pub fn set_max_bytes_per_user(&mut self, max_bytes_per_user: u32) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.max_bytes_per_user = max_bytes_per_user;
+            Ok(())
+        }
+
+        /// Marks a task done and moves it to the bottom of the list, in a single write
+        #[ink(message)]
+        pub fn complete_and_sink(&mut self, user: AccountId, title: String) -> Result<(), Error> {
+            self.ensure_writable(user)?;
+
+            let mut tasks = self.get_task(user);
+            let position = tasks
+                .iter()
+                .position(|t| t.title == title)
+                .ok_or(Error::TaskNotFound)?;
+            let mut task = tasks.remove(position);
+            self.set_state(user, &mut task, TaskState::Done);
+            tasks.push(task);
+
+            self.store(user, &tasks)?;
+            Ok(())
+        }
+
+        /// Fetches the tasks of a user that have a due date
+        #[ink(message)]
+        pub fn scheduled_tasks(&self, user: AccountId) -> Vec<Task> {
+            self.get_task(user)
+                .into_iter()
+                .filter(|t| t.due.is_some())
+                .collect()
+        }
+
+        /// Fetches the archived tasks of a user
+        #[ink(message)]
+        pub fn get_archived(&self, user: AccountId) -> Vec<Task> {
+            self.archived.get(user).unwrap_or_default()
+        }
+
+        /// Archives the done tasks of a user completed more than `age_ms` ago,
+        /// returning how many were archived; archiving cannot be undone
+        #[ink(message)]
+        pub fn auto_archive_done_older_than(
+            &mut self,
+            user: AccountId,
+            age_ms: u64,
+        ) -> Result<u32, Error> {
+            self.ensure_writable(user)?;
+
+            let cutoff = self.env().block_timestamp().saturating_sub(age_ms);
+            let (old, tasks): (Vec<Task>, Vec<Task>) =
+                self.get_task(user).into_iter().partition(|t| {
+                    t.state == TaskState::Done && t.completed_at.is_some_and(|at| at < cutoff)
+                });
+            if old.is_empty() {
+                return Ok(0);
+            }
+
+            let count = old.len() as u32;
+            let mut archived = self.get_archived(user);
+            archived.extend(old);
+            self.archived.insert(user, &archived);
+            self.store(user, &tasks)?;
+            self.record_removals(user, count as u64);
+            self.last_snapshot.remove(user);
+            Ok(count)
+        }
+
+        /// Fetches the titles of a user within `max_distance` edits of the query
+        #[ink(message)]
+        pub fn find_similar_titles(
+            &self,
+            user: AccountId,
+            query: String,
+            max_distance: u32,
+        ) -> Vec<String> {
+            self.get_task(user)
+                .into_iter()
+                .filter(|t| Self::edit_distance(&t.title, &query) <= max_distance)
+                .map(|t| t.title)
+                .collect()
+        }
+
+        /// Counts the tasks of a user in the given state, if any, restricted to
+        /// tasks with a due date when `has_due` is set
+        #[ink(message)]
+        pub fn count_where(&self, user: AccountId, state: Option<TaskState>, has_due: bool) -> u32 {
+            self.get_task(user)
+                .iter()
+                .filter(|t| state.is_none_or(|state| t.state == state))
+                .filter(|t| !has_due || t.due.is_some())
+                .count() as u32
+        }
+
+        /// Fetches a page of the tasks of a user along with their total count
+        #[ink(message)]
+        pub fn get_page(&self, user: AccountId, offset: u32, limit: u32) -> (Vec<Task>, u32) {
+            let tasks = self.get_task(user);
+            let total = tasks.len() as u32;
+            let page = tasks
+                .into_iter()
+                .skip(offset as usize)
+                .take(limit as usize)
+                .collect();
+            (page, total)
+        }
+
+        /// Sets how many Wip tasks a list can hold, 0 meaning unlimited
+        #[ink(message)]
+        pub fn set_max_wip(&mut self, max_wip: u32) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.max_wip = max_wip;
+            Ok(())
+        }
+
+        /// Overrides `max_wip` for a single user
+        #[ink(message)]
+        pub fn set_user_wip_limit(&mut self, user: AccountId, limit: u32) -> Result<(), Error> {
+            self.ensure_owner_or_manager()?;
+            let settings = UserSettings {
+                wip_limit: Some(limit),
+                ..self.get_user_settings(user)
+            };
+            self.settings.insert(user, &settings);
+            Ok(())
+        }
+
+        /// Fetches the users having at least `n` tasks
+        #[ink(message)]
+        pub fn users_with_at_least(&self, n: u32) -> Result<Vec<AccountId>, Error> {
+            self.ensure_owner()?;
+            Ok(self
+                .all_users()
+                .into_iter()
+                .filter(|user| self.get_task(*user).len() as u32 >= n)
+                .collect())
+        }
+
+        /// Display name of a state, for clients that do not decode enums
+        #[ink(message)]
+        pub fn state_name(&self, state: TaskState) -> String {
+            state.name().into()
+        }
+
+        /// Moves the task with the given id from one list to another, keeping
+        /// its id and metadata, which cannot be undone; callable by `from` or a
+        /// manager
+        #[ink(message)]
+        pub fn relocate_task(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            id: u64,
+        ) -> Result<(), Error> {
+            if self.env().caller() != from {
+                self.ensure_owner_or_manager()?;
+            }
+            self.ensure_writable(from)?;
+            self.ensure_writable(to)?;
+
+            let mut from_tasks = self.get_task(from);
+            let mut to_tasks = self.get_task(to);
+
+            let position = from_tasks
+                .iter()
+                .position(|t| t.id == id)
+                .ok_or(Error::TaskNotFound)?;
+            if to_tasks
+                .iter()
+                .any(|t| t.title == from_tasks[position].title)
+            {
+                return Err(Error::DuplicateTitle);
+            }
+
+            let moved = from_tasks.remove(position);
+            if moved.state == TaskState::Wip {
+                self.ensure_wip_capacity(to, Self::count_states(&to_tasks).1)?;
+            }
+            to_tasks.push(moved);
+            self.ensure_quota(to, &to_tasks)?;
+            self.write_tasks(from, &from_tasks);
+            self.write_tasks(to, &to_tasks);
+            self.last_snapshot.remove(from);
+            self.last_snapshot.remove(to);
+            self.record_removals(from, 1);
+            self.record_adds(to, 1);
+            self.emit_mutation(from, OP_REMOVE);
+            self.emit_mutation(to, OP_ADD);
+            Ok(())
+        }
+
+        /// Fetches the tasks of a user as `(title, state)` pairs, the state
+        /// being encoded with `TaskState::as_u8`
+        #[ink(message)]
+        pub fn compact_list(&self, user: AccountId) -> Vec<(String, u8)> {
+            self.get_task(user)
+                .into_iter()
+                .map(|t| (t.title, t.state.as_u8()))
+                .collect()
+        }
+
+        /// Registers the contract to call on `notify(user, title)` for the
+        /// caller's overdue tasks
+        #[ink(message)]
+        pub fn set_reminder_contract(&mut self, contract: AccountId) {
+            self.reminders.insert(self.env().caller(), &contract);
+        }
+
+        /// Calls the reminder contract of a user for each of their overdue
+        /// tasks, ignoring any failure, and returns how many calls were made
+        #[ink(message)]
+        pub fn fire_reminders(&mut self, user: AccountId) -> u32 {
+            let Some(contract) = self.reminders.get(user) else {
+                return 0;
+            };
+
+            let now = self.env().block_timestamp();
+            let mut fired = 0;
+            for task in self
+                .get_task(user)
+                .into_iter()
+                .filter(|t| t.is_overdue(now))
+            {
+                let _ = build_call::<Environment>()
+                    .call(contract)
+                    .gas_limit(0)
+                    .exec_input(
+                        ExecutionInput::new(Selector::new(ink::selector_bytes!("notify")))
+                            .push_arg(user)
+                            .push_arg(task.title),
+                    )
+                    .returns::<()>()
+                    .try_invoke();
+                fired += 1;
+            }
+            fired
+        }
+
+        /// Removes the empty lists left in storage, along with their users from
+        /// the users index, and returns how many were reclaimed
+        #[ink(message)]
+        pub fn cleanup_empty_entries(&mut self) -> Result<u32, Error> {
+            self.ensure_owner()?;
+
+            let (empty, users): (Vec<AccountId>, Vec<AccountId>) = self
+                .all_users()
+                .into_iter()
+                .partition(|user| self.tasks.get(*user).is_some_and(|t| t.is_empty()));
+            for user in &empty {
+                self.tasks.remove(user);
+                self.state_counts.remove(user);
+                self.user_index.remove(user);
+            }
+            for (i, user) in users.iter().enumerate() {
+                self.users.insert(i as u32, user);
+                self.user_index.insert(user, &(i as u32));
+            }
+            for i in users.len() as u32..self.user_count {
+                self.users.remove(i);
+            }
+            self.user_count = users.len() as u32;
+            Ok(empty.len() as u32)
+        }
+
+        /// Total estimated effort of the tasks of a user that are not done
+        #[ink(message)]
+        pub fn remaining_effort(&self, user: AccountId) -> u32 {
+            self.get_task(user)
+                .iter()
+                .filter(|t| t.state != TaskState::Done)
+                .map(|t| t.effort_minutes)
+                .sum()
+        }
+
+        /// Number of tasks of a user that are not done
+        #[ink(message)]
+        pub fn open_task_count(&self, user: AccountId) -> u32 {
+            self.get_task(user)
+                .iter()
+                .filter(|t| t.state != TaskState::Done)
+                .count() as u32
+        }
+
+        /// Appends the current open task count of a user to their burndown,
+        /// dropping the oldest point past `MAX_BURNDOWN_POINTS`
+        #[ink(message)]
+        pub fn record_burndown(&mut self, user: AccountId) -> Result<(), Error> {
+            self.ensure_owner()?;
+
+            let mut burndown = self.get_burndown(user);
+            if burndown.len() >= MAX_BURNDOWN_POINTS {
+                burndown.remove(0);
+            }
+            burndown.push((self.env().block_timestamp(), self.open_task_count(user)));
+            self.burndown.insert(user, &burndown);
+            Ok(())
+        }
+
+        /// Fetches the recorded `(timestamp, open tasks)` points of a user
+        #[ink(message)]
+        pub fn get_burndown(&self, user: AccountId) -> Vec<BurndownPoint> {
+            self.burndown.get(user).unwrap_or_default()
+        }
+
+        /// Rearranges the tasks of a user to follow the given title order
+        #[ink(message)]
+        pub fn reorder(
+            &mut self,
+            user: AccountId,
+            ordered_titles: Vec<String>,
+        ) -> Result<(), Error> {
+            self.ensure_writable(user)?;
+
+            let mut remaining = self.get_task(user);
+            let mut tasks = Vec::with_capacity(remaining.len());
+            for title in ordered_titles {
+                let position = remaining
+                    .iter()
+                    .position(|t| t.title == title)
+                    .ok_or(Error::InvalidOrdering)?;
+                tasks.push(remaining.remove(position));
+            }
+            if !remaining.is_empty() {
+                return Err(Error::InvalidOrdering);
+            }
+
+            self.store(user, &tasks)?;
+            Ok(())
+        }
+
+        /// Title and due date of the open task of a user due the soonest
+        #[ink(message)]
+        pub fn next_deadline(&self, user: AccountId) -> Option<(String, u64)> {
+            self.get_task(user)
+                .into_iter()
+                .filter(|t| t.state != TaskState::Done)
+                .filter_map(|t| t.due.map(|due| (t.title, due)))
+                .min_by_key(|(_, due)| *due)
+        }
+
+        /// Marks the caller's list as public or private
+        #[ink(message)]
+        pub fn set_list_public(&mut self, is_public: bool) {
+            let caller = self.env().caller();
+            if is_public {
+                self.public.insert(caller, &());
+            } else {
+                self.public.remove(caller);
+            }
+        }
+
+        /// Copies the tasks of a public list into the caller's list, as new
+        /// unassigned Todo tasks, and returns how many were copied
+        #[ink(message)]
+        pub fn clone_list_from(&mut self, source: AccountId) -> Result<u32, Error> {
+            if !self.public.contains(source) {
+                return Err(Error::NotAuthorized);
+            }
+
+            let copies = self
+                .get_task(source)
+                .into_iter()
+                .map(|t| Task {
+                    state: TaskState::Todo,
+                    overdue_flagged: false,
+                    completed_at: None,
+                    assignee: None,
+                    ..t
+                })
+                .collect();
+            self.add_batch(self.env().caller(), copies)
+        }
+
+        /// Fetches the tasks of a user whose list is public
+        #[ink(message)]
+        pub fn get_public_tasks(&self, owner: AccountId) -> Result<Vec<Task>, Error> {
+            if !self.public.contains(owner) {
+                return Err(Error::NotAuthorized);
+            }
+            Ok(self.get_task(owner))
+        }
+
+        /// Counts the tasks of a user per priority, as `(high, medium, low)`
+        #[ink(message)]
+        pub fn count_by_priority(&self, user: AccountId) -> (u32, u32, u32) {
+            self.get_task(user)
+                .iter()
+                .fold((0, 0, 0), |(high, medium, low), task| match task.priority {
+                    Priority::High => (high + 1, medium, low),
+                    Priority::Medium => (high, medium + 1, low),
+                    Priority::Low => (high, medium, low + 1),
+                })
+        }
+
+        /// Sets the priority of every task of a user in the given state,
+        /// returning how many tasks changed
+        #[ink(message)]
+        pub fn set_priority_for_state(
+            &mut self,
+            user: AccountId,
+            state: TaskState,
+            priority: Priority,
+        ) -> Result<u32, Error> {
+            self.ensure_writable(user)?;
+
+            let now = self.env().block_timestamp();
+            let mut tasks = self.get_task(user);
+            let mut changed = 0;
+            for task in tasks
+                .iter_mut()
+                .filter(|t| t.state == state && t.priority != priority)
+            {
+                task.priority = priority;
+                task.updated_at = now;
+                changed += 1;
+            }
+
+            if changed > 0 {
+                self.store(user, &tasks)?;
+            }
+            Ok(changed)
+        }
+
+        /// Fetches the tasks of a user, failing if they never had a list
+        #[ink(message)]
+        pub fn get_task_strict(&self, user: AccountId) -> Result<Vec<Task>, Error> {
+            self.tasks.get(user).ok_or(Error::UserNotFound)
+        }
+
+        /// Moves the first Todo task of a user to Wip and returns it, unless the
+        /// list is at its WIP limit
+        #[ink(message)]
+        pub fn start_next(&mut self, user: AccountId) -> Option<Task> {
+            self.ensure_writable(user).ok()?;
+
+            let mut tasks = self.get_task(user);
+            let (_, wip, _) = Self::count_states(&tasks);
+            self.ensure_wip_capacity(user, wip).ok()?;
+
+            let task = tasks.iter_mut().find(|t| t.state == TaskState::Todo)?;
+            self.set_state(user, task, TaskState::Wip);
+            let started = task.clone();
+
+            self.store(user, &tasks).ok()?;
+            Some(started)
+        }
+
+        /// Fetches the Wip tasks of a user not updated for more than `older_than_ms`
+        #[ink(message)]
+        pub fn stale_wip(&self, user: AccountId, older_than_ms: u64) -> Vec<Task> {
+            let cutoff = self.env().block_timestamp().saturating_sub(older_than_ms);
+            self.get_task(user)
+                .into_iter()
+                .filter(|t| t.state == TaskState::Wip && t.updated_at < cutoff)
+                .collect()
+        }
+
+        /// Pauses every list for an upgrade and returns the total number of
+        /// tasks, as a checksum for the migration
+        #[ink(message)]
+        pub fn prepare_upgrade(&mut self) -> Result<u64, Error> {
+            self.ensure_owner()?;
+            self.paused = true;
+            Ok(self
+                .all_users()
+                .iter()
+                .map(|user| self.get_task(*user).len() as u64)
+                .sum())
+        }
+
+        /// Ends the upgrade window opened by `prepare_upgrade`
+        #[ink(message)]
+        pub fn finish_upgrade(&mut self) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.paused = false;
+            Ok(())
+        }
+
+        /// Fetches the titles of the tasks of a user lacking a description or
+        /// a due date
+        #[ink(message)]
+        pub fn incomplete_metadata(&self, user: AccountId) -> Vec<String> {
+            self.get_task(user)
+                .into_iter()
+                .filter(|t| t.description.is_empty() || t.due.is_none())
+                .map(|t| t.title)
+                .collect()
+        }
+
+        /// Folds the description, tags and subtasks of one task into another task of the
+        /// same list, then removes it
+        #[ink(message)]
+        pub fn merge_tasks(
+            &mut self,
+            user: AccountId,
+            keep_title: String,
+            merge_title: String,
+        ) -> Result<(), Error> {
+            self.ensure_writable(user)?;
+
+            let mut tasks = self.get_task(user);
+            let keep = tasks
+                .iter()
+                .position(|t| t.title == keep_title)
+                .ok_or(Error::TaskNotFound)?;
+            let merge = tasks
+                .iter()
+                .enumerate()
+                .position(|(i, t)| i != keep && t.title == merge_title)
+                .ok_or(Error::TaskNotFound)?;
+
+            let merged = tasks.remove(merge);
+            let kept = &mut tasks[if merge < keep { keep - 1 } else { keep }];
+            if !merged.description.is_empty() {
+                if !kept.description.is_empty() {
+                    kept.description.push('\n');
+                }
+                kept.description.push_str(&merged.description);
+                self.fit_description(&mut kept.description)?;
+            }
+            for tag in merged.tags {
+                if !kept.tags.contains(&tag) {
+                    if kept.tags.len() >= MAX_TAGS_PER_TASK {
+                        return Err(Error::TagLimitReached);
+                    }
+                    kept.tags.push(tag);
+                }
+            }
+            kept.subtasks.extend(merged.subtasks);
+            kept.updated_at = self.env().block_timestamp();
+
+            self.store(user, &tasks)?;
+            self.record_removals(user, 1);
+            Ok(())
+        }
+
+        /// Returns `(task_count, users)` pairs telling how many users have each
+        /// task count, by increasing task count
+        #[ink(message)]
+        pub fn count_distribution(&self) -> Result<Vec<(u32, u32)>, Error> {
+            self.ensure_owner()?;
+
+            let mut distribution: Vec<(u32, u32)> = Vec::new();
+            for user in &self.all_users() {
+                let count = self.get_task(*user).len() as u32;
+                match distribution.binary_search_by_key(&count, |(c, _)| *c) {
+                    Ok(i) => distribution[i].1 += 1,
+                    Err(i) => distribution.insert(i, (count, 1)),
+                }
+            }
+            Ok(distribution)
+        }
+
+        /// Sets the task count past which `add_task_checked` warns, 0 meaning never
+        #[ink(message)]
+        pub fn set_soft_task_limit(&mut self, soft_task_limit: u32) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.soft_task_limit = soft_task_limit;
+            Ok(())
+        }
+
+        /// Adds a task like `add_task`, returning the new task count, whether it
+        /// is past the soft task limit and the length of the stored description
+        #[ink(message)]
+        pub fn add_task_checked(
+            &mut self,
+            user: AccountId,
+            task: Task,
+        ) -> Result<(u32, bool, u32), Error> {
+            self.add_task(user, task)?;
+            let tasks = self.get_task(user);
+            let count = tasks.len() as u32;
+            let soft_task_limit = self
+                .get_user_settings(user)
+                .soft_task_limit
+                .unwrap_or(self.soft_task_limit);
+            let description_len = tasks.last().map_or(0, |t| t.description.len() as u32);
+            Ok((
+                count,
+                soft_task_limit != 0 && count > soft_task_limit,
+                description_len,
+            ))
+        }
+
+        /// Whether a user has tasks and all of them are done
+        #[ink(message)]
+        pub fn all_done(&self, user: AccountId) -> bool {
+            let tasks = self.get_task(user);
+            !tasks.is_empty() && tasks.iter().all(|t| t.state == TaskState::Done)
+        }
+
+        /// Moves every task of a user back to Todo, returning how many changed
+        #[ink(message)]
+        pub fn reset_all_to_todo(&mut self, user: AccountId) -> Result<u32, Error> {
+            self.ensure_writable(user)?;
+
+            let mut tasks = self.get_task(user);
+            let mut changed = 0;
+            for task in tasks.iter_mut().filter(|t| t.state != TaskState::Todo) {
+                self.set_state(user, task, TaskState::Todo);
+                changed += 1;
+            }
+
+            if changed > 0 {
+                self.store(user, &tasks)?;
+            }
+            Ok(changed)
+        }
+
+        /// Turns a checklist item of a task into a standalone Todo task
+        #[ink(message)]
+        pub fn promote_subtask(
+            &mut self,
+            user: AccountId,
+            parent_title: String,
+            item: String,
+        ) -> Result<(), Error> {
+            self.ensure_writable(user)?;
+
+            let mut tasks = self.get_task(user);
+            let parent = Self::find_task(&mut tasks, &parent_title)?;
+            let position = parent
+                .subtasks
+                .iter()
+                .position(|s| *s == item)
+                .ok_or(Error::TaskNotFound)?;
+            parent.subtasks.remove(position);
+            parent.updated_at = self.env().block_timestamp();
+
+            let task = Task::new(item.clone(), String::new(), TaskState::Todo);
+            self.insert_into(user, &mut tasks, usize::MAX, task)?;
+            self.store(user, &tasks)?;
+            self.emit(TaskAdded { user, title: item });
+            Ok(())
+        }
+
+        /// Open task of a user that was added the earliest
+        #[ink(message)]
+        pub fn oldest_open(&self, user: AccountId) -> Option<Task> {
+            self.get_task(user)
+                .into_iter()
+                .filter(|t| t.state != TaskState::Done)
+                .min_by_key(|t| t.created_at)
+        }
+
+        /// Gives every open undated task of a user a due date `offset_ms` from
+        /// now, returning how many were scheduled
+        #[ink(message)]
+        pub fn schedule_all(&mut self, user: AccountId, offset_ms: u64) -> Result<u32, Error> {
+            self.ensure_writable(user)?;
+
+            let now = self.env().block_timestamp();
+            let mut tasks = self.get_task(user);
+            let mut scheduled = 0;
+            for task in tasks
+                .iter_mut()
+                .filter(|t| t.state != TaskState::Done && t.due.is_none())
+            {
+                task.due = Some(now.saturating_add(offset_ms));
+                task.updated_at = now;
+                scheduled += 1;
+            }
+
+            if scheduled > 0 {
+                self.store(user, &tasks)?;
+            }
+            Ok(scheduled)
+        }
+
+        /// Fetches the limits overridden for a user
+        #[ink(message)]
+        pub fn get_user_settings(&self, user: AccountId) -> UserSettings {
+            self.settings.get(user).unwrap_or_default()
+        }
+
+        /// Overrides the contract-wide limits for a single user
+        #[ink(message)]
+        pub fn set_user_settings(
+            &mut self,
+            user: AccountId,
+            settings: UserSettings,
+        ) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.settings.insert(user, &settings);
+            Ok(())
+        }
+
+        /// Returns how many tasks were ever `(added, removed)` for a user
+        #[ink(message)]
+        pub fn lifetime_stats(&self, user: AccountId) -> (u64, u64) {
+            (
+                self.added_total.get(user).unwrap_or(0),
+                self.removed_total.get(user).unwrap_or(0),
+            )
+        }
+
+        /// Fetches the titles of a user starting with the given prefix
+        #[ink(message)]
+        pub fn titles_with_prefix(&self, user: AccountId, prefix: String) -> Vec<String> {
+            self.get_task(user)
+                .into_iter()
+                .filter(|t| t.title.starts_with(&prefix))
+                .map(|t| t.title)
+                .collect()
+        }
+
+        /// Adds a task at the given position of a list, clamped to its end
+        #[ink(message)]
+        pub fn insert_task_at(
+            &mut self,
+            user: AccountId,
+            index: u32,
+            task: Task,
+        ) -> Result<(), Error> {
+            if self.get_task(user).iter().any(|t| t.title == task.title) {
+                return Err(Error::DuplicateTitle);
+            }
+
+            let title = task.title.clone();
+            self.insert_task(user, index as usize, task)?;
+            self.emit_mutation(user, OP_ADD);
+            self.emit(TaskAdded { user, title });
+            Ok(())
+        }
+
+        /// Returns the `(task_count, estimated_storage_bytes)` of a user
+        #[ink(message)]
+        pub fn footprint(&self, user: AccountId) -> (u32, u32) {
+            (self.task_count(user), self.estimated_storage_bytes(user))
+        }
+
+        /// Counts the tasks assigned to an account across all lists
+        #[ink(message)]
+        pub fn tasks_assigned_count(&self, assignee: AccountId) -> Result<u32, Error> {
+            self.ensure_owner()?;
+            Ok(self
+                .all_users()
+                .iter()
+                .flat_map(|user| self.get_task(*user))
+                .filter(|t| t.assignee == Some(assignee))
+                .count() as u32)
+        }
+
+        /// Attaches a tag to a task if it is missing and detaches it otherwise,
+        /// returning whether the task now carries the tag
+        #[ink(message)]
+        pub fn toggle_tag(
+            &mut self,
+            user: AccountId,
+            title: String,
+            tag: String,
+        ) -> Result<bool, Error> {
+            self.ensure_writable(user)?;
+
+            let mut tasks = self.get_task(user);
+            let task = Self::find_task(&mut tasks, &title)?;
+            let present = match task.tags.iter().position(|t| *t == tag) {
+                Some(position) => {
+                    task.tags.remove(position);
+                    false
+                }
+                None if task.tags.len() >= MAX_TAGS_PER_TASK => {
+                    return Err(Error::TagLimitReached);
+                }
+                None => {
+                    task.tags.push(tag);
+                    true
+                }
+            };
+            task.updated_at = self.env().block_timestamp();
+
+            self.store(user, &tasks)?;
+            Ok(present)
+        }
+
+        /// Percentages of the tasks of a user in each state, as `(todo, wip, done)`,
+        /// the rounding remainder going to the largest bucket
+        #[ink(message)]
+        pub fn state_percentages(&self, user: AccountId) -> (u8, u8, u8) {
+            let (todo, wip, done) = Self::count_states(&self.get_task(user));
+            let total = todo + wip + done;
+            if total == 0 {
+                return (0, 0, 0);
+            }
+
+            let mut percents = [todo, wip, done].map(|count| count * 100 / total);
+            let largest = if todo >= wip && todo >= done {
+                0
+            } else if wip >= done {
+                1
+            } else {
+                2
+            };
+            percents[largest] += 100 - percents.iter().sum::<u32>();
+            (percents[0] as u8, percents[1] as u8, percents[2] as u8)
+        }
+
+        /// Returns the `(user, title)` of every open task not updated for more
+        /// than `older_than_ms`, across all lists
+        #[ink(message)]
+        pub fn global_stale(&self, older_than_ms: u64) -> Result<Vec<(AccountId, String)>, Error> {
+            self.ensure_owner()?;
+
+            let cutoff = self.env().block_timestamp().saturating_sub(older_than_ms);
+            let mut stale = Vec::new();
+            for user in &self.all_users() {
+                for task in self.get_task(*user) {
+                    if task.state != TaskState::Done && task.updated_at < cutoff {
+                        stale.push((*user, task.title));
+                    }
+                }
+            }
+            Ok(stale)
+        }
+
+        /// Role of the caller: 0 for a regular user, 1 for a manager, 2 for the owner
+        #[ink(message)]
+        pub fn my_role(&self) -> u8 {
+            let caller = self.env().caller();
+            if caller == self.owner {
+                2
+            } else if self.managers.contains(caller) {
+                1
+            } else {
+                0
+            }
+        }
+
+        /// Removes the tasks of a user added before `cutoff_created_at`,
+        /// returning how many were removed
+        #[ink(message)]
+        pub fn prune_older_than(
+            &mut self,
+            user: AccountId,
+            cutoff_created_at: u64,
+        ) -> Result<u32, Error> {
+            self.ensure_writable(user)?;
+
+            let mut tasks = self.get_task(user);
+            let before = tasks.len();
+            tasks.retain(|t| t.created_at >= cutoff_created_at);
+            let pruned = (before - tasks.len()) as u32;
+
+            if pruned > 0 {
+                self.store(user, &tasks)?;
+                self.record_removals(user, pruned as u64);
+            }
+            Ok(pruned)
+        }
+
+        /// Whether no task of a user, archived ones included, has the given title
+        #[ink(message)]
+        pub fn title_available(&self, user: AccountId, title: String) -> bool {
+            !self.task_exists(user, title.clone())
+                && !self.get_archived(user).iter().any(|t| t.title == title)
+        }
+
+        /// Reorders the list of a user by ascending due date, undated tasks
+        /// going last in their current order
+        #[ink(message)]
+        pub fn sort_by_due(&mut self, user: AccountId) -> Result<(), Error> {
+            self.ensure_writable(user)?;
+
+            let mut tasks = self.get_task(user);
+            tasks.sort_by_key(|t| (t.due.is_none(), t.due));
+            self.store(user, &tasks)?;
+            Ok(())
+        }
+
+        /// Id the next added task will get
+        #[ink(message)]
+        pub fn peek_next_id(&self) -> u64 {
+            self.next_id
+        }
+
+        /// Marks the tasks of a user with the given ids as done, returning how
+        /// many changed; unknown ids are skipped
+        #[ink(message)]
+        pub fn complete_ids(&mut self, user: AccountId, ids: Vec<u64>) -> Result<u32, Error> {
+            self.ensure_writable(user)?;
+
+            let mut tasks = self.get_task(user);
+            let mut completed = 0;
+            for task in tasks
+                .iter_mut()
+                .filter(|t| ids.contains(&t.id) && t.state != TaskState::Done)
+            {
+                self.set_state(user, task, TaskState::Done);
+                completed += 1;
+            }
+
+            if completed > 0 {
+                self.store(user, &tasks)?;
+            }
+            Ok(completed)
+        }
+
+        fn ensure_owner(&self) -> Result<(), Error> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            Ok(())
+        }
+
+        fn ensure_owner_or_manager(&self) -> Result<(), Error> {
+            let caller = self.env().caller();
+            if caller != self.owner && !self.managers.contains(caller) {
+                return Err(Error::NotAuthorized);
+            }
+            Ok(())
+        }
+
+        /// Fails if a list already holding `wip` Wip tasks cannot take another
+        fn ensure_wip_capacity(&self, user: AccountId, wip: u32) -> Result<(), Error> {
+            let limit = self
+                .get_user_settings(user)
+                .wip_limit
+                .unwrap_or(self.max_wip);
+            if limit != 0 && wip >= limit {
+                return Err(Error::WipLimitReached);
+            }
+            Ok(())
+        }
+
+        fn ensure_writable(&self, user: AccountId) -> Result<(), Error> {
+            if self.paused {
+                return Err(Error::Paused);
+            }
+            if self.frozen.contains(user) {
+                return Err(Error::UserFrozen);
+            }
+            Ok(())
+        }
+
+        /// Appends a task to a list, stamping its id and creation time, without
+        /// emitting `MutationApplied`
+        fn push_task(&mut self, user: AccountId, task: Task) -> Result<(), Error> {
+            self.insert_task(user, usize::MAX, task)
+        }
+
+        /// Inserts a task at the given index of a list, clamped to its end,
+        /// stamping its id and creation time, without emitting `MutationApplied`
+        fn insert_task(&mut self, user: AccountId, index: usize, task: Task) -> Result<(), Error> {
+            let mut user_task = self.get_task(user);
+            self.insert_into(user, &mut user_task, index, task)?;
+            self.write_tasks(user, &user_task);
+            Ok(())
+        }
+
+        /// Inserts a task at the given index of a list of a user that is not
+        /// written yet, running the checks and bookkeeping of `insert_task`
+        fn insert_into(
+            &mut self,
+            user: AccountId,
+            user_task: &mut Vec<Task>,
+            index: usize,
+            mut task: Task,
+        ) -> Result<(), Error> {
+            self.ensure_writable(user)?;
+            self.fit_description(&mut task.description)?;
+            self.validate_task(&task)?;
+            self.check_rate_limit(user)?;
+
+            if task.state == TaskState::Wip {
+                self.ensure_wip_capacity(user, Self::count_states(user_task).1)?;
+            }
+            self.stamp_completion(&mut task);
+            let index = index.min(user_task.len());
+            user_task.insert(
+                index,
+                Task {
+                    id: self.next_id,
+                    created_at: self.env().block_timestamp(),
+                    updated_at: self.env().block_timestamp(),
+                    ..task
+                },
+            );
+            self.ensure_quota(user, user_task)?;
+            self.next_id += 1;
+            self.record_adds(user, 1);
+            Ok(())
+        }
+
+        /// Adds several tasks in a single write, emitting either one `TaskAdded`
+        /// per task or a single `BulkAdded` depending on `aggregate_batch_events`
+        fn add_batch(&mut self, user: AccountId, tasks: Vec<Task>) -> Result<u32, Error> {
+            let count = tasks.len() as u32;
+            if count == 0 {
+                return Ok(0);
+            }
+
+            let mut list = self.get_task(user);
+            let mut titles = Vec::new();
+            for task in tasks {
+                titles.push(task.title.clone());
+                self.insert_into(user, &mut list, usize::MAX, task)?;
+            }
+            self.store(user, &list)?;
+
+            if self.aggregate_batch_events {
+                self.emit(BulkAdded { user, count });
+            } else {
+                for title in titles {
+                    self.emit(TaskAdded { user, title });
+                }
+            }
+            Ok(count)
+        }
+
+        /// Gives a task written as done a completion time if it lacks one, and
+        /// clears it on a task that is not done
+        fn stamp_completion(&self, task: &mut Task) {
+            if task.state == TaskState::Done {
+                task.completed_at
+                    .get_or_insert(self.env().block_timestamp());
+            } else {
+                task.completed_at = None;
+            }
+        }
+
+        /// Checks a task against the rules enforced on new tasks
+        fn validate_task(&self, task: &Task) -> Result<(), Error> {
+            Self::validate_title(&task.title)?;
+            if !self.allow_empty_description && task.description.is_empty() {
+                return Err(Error::InvalidDescription);
+            }
+            if task.description.len() > MAX_DESCRIPTION_LEN {
+                return Err(Error::DescriptionTooLong);
+            }
+            if task.tags.len() > MAX_TAGS_PER_TASK {
+                return Err(Error::TagLimitReached);
+            }
+            Ok(())
+        }
+
+        /// Checks a whole list written at once against the rules enforced on
+        /// new tasks, its titles being unique and its user's limits respected
+        fn validate_list(&self, user: AccountId, tasks: &[Task]) -> Result<(), Error> {
+            for (i, task) in tasks.iter().enumerate() {
+                if tasks[..i].iter().any(|t| t.title == task.title) {
+                    return Err(Error::DuplicateTitle);
+                }
+                self.validate_task(task)?;
+            }
+
+            let (_, wip, _) = Self::count_states(tasks);
+            if wip > 0 {
+                self.ensure_wip_capacity(user, wip - 1)?;
+            }
+            self.ensure_quota(user, tasks)
+        }
+
+        /// Fails if a list would take more bytes than its user is allowed
+        fn ensure_quota(&self, user: AccountId, tasks: &[Task]) -> Result<(), Error> {
+            let max_bytes = self
+                .get_user_settings(user)
+                .max_bytes
+                .unwrap_or(self.max_bytes_per_user);
+            if max_bytes != 0 && tasks.encoded_size() as u32 > max_bytes {
+                return Err(Error::QuotaExceeded);
+            }
+            Ok(())
+        }
+
+        /// Cuts a description down to the length cap when truncation is enabled,
+        /// failing if it is still too long
+        fn fit_description(&self, description: &mut String) -> Result<(), Error> {
+            if self.truncate_long_descriptions && description.len() > MAX_DESCRIPTION_LEN {
+                let mut len = MAX_DESCRIPTION_LEN;
+                while !description.is_char_boundary(len) {
+                    len -= 1;
+                }
+                description.truncate(len);
+            }
+            if description.len() > MAX_DESCRIPTION_LEN {
+                return Err(Error::DescriptionTooLong);
+            }
+            Ok(())
+        }
+
+        /// Rejects titles with control characters, which break the text export
+        fn validate_title(title: &str) -> Result<(), Error> {
+            if title.chars().any(|c| c.is_ascii_control()) {
+                return Err(Error::InvalidTitle);
+            }
+            Ok(())
+        }
+
+        /// Writes the task list of a user and emits `MutationApplied`, for
+        /// messages that write a single list once; fails if the write grows the
+        /// list past its byte quota
+        fn store(&mut self, user: AccountId, tasks: &Vec<Task>) -> Result<(), Error> {
+            if tasks.encoded_size() > self.get_task(user).encoded_size() {
+                self.ensure_quota(user, tasks)?;
+            }
+            let op = self.write_tasks(user, tasks);
+            self.emit_mutation(user, op);
+            Ok(())
+        }
+
+        /// Every account in the users index, by registration order
+        fn all_users(&self) -> Vec<AccountId> {
+            (0..self.user_count)
+                .filter_map(|i| self.users.get(i))
+                .collect()
+        }
+
+        /// Writes the task list of a user, registering them in the users index
+        /// and keeping the previous list for `undo`, returning the op code of
+        /// the write
+        fn write_tasks(&mut self, user: AccountId, tasks: &Vec<Task>) -> u8 {
+            if !self.user_index.contains(user) {
+                self.users.insert(self.user_count, &user);
+                self.user_index.insert(user, &self.user_count);
+                self.user_count += 1;
+            }
+            let previous = self.tasks.get(user).unwrap_or_default();
+            let op = match tasks.len().cmp(&previous.len()) {
+                core::cmp::Ordering::Greater => OP_ADD,
+                core::cmp::Ordering::Less => OP_REMOVE,
+                core::cmp::Ordering::Equal => OP_UPDATE,
+            };
+            self.last_snapshot.insert(user, &previous);
+            self.tasks.insert(user, tasks);
+            self.state_counts.insert(user, &Self::count_states(tasks));
+            self.last_modified_block
+                .insert(user, &(self.env().block_number() as u64));
+            op
+        }
+
+        /// Emits the `MutationApplied` of a message that wrote the list of a user
+        fn emit_mutation(&self, user: AccountId, op: u8) {
+            let (todo, wip, done) = self.count_by_state(user);
+            self.emit(MutationApplied {
+                user,
+                op,
+                new_count: todo + wip + done,
+            });
+        }
+
+        fn count_states(tasks: &[Task]) -> (u32, u32, u32) {
+            tasks
+                .iter()
+                .fold((0, 0, 0), |(todo, wip, done), task| match task.state {
+                    TaskState::Todo => (todo + 1, wip, done),
+                    TaskState::Wip => (todo, wip + 1, done),
+                    TaskState::Done => (todo, wip, done + 1),
+                })
+        }
+
+        /// Changes the state of a task, running the hooks of the transition
+        fn set_state(&mut self, user: AccountId, task: &mut Task, state: TaskState) {
+            if state == TaskState::Done && task.state != TaskState::Done {
+                task.completed_at = Some(self.env().block_timestamp());
+                self.record_completion(user);
+                self.notify_rewards(user);
+            } else if state != TaskState::Done {
+                task.completed_at = None;
+            }
+            task.state = state;
+            task.updated_at = self.env().block_timestamp();
+        }
+
+        /// Calls `reward(user)` on the rewards contract, ignoring any failure
+        fn notify_rewards(&self, user: AccountId) {
+            if let Some(rewards_contract) = self.rewards_contract {
+                let _ = build_call::<Environment>()
+                    .call(rewards_contract)
+                    .gas_limit(0)
+                    .exec_input(
+                        ExecutionInput::new(Selector::new(ink::selector_bytes!("reward")))
+                            .push_arg(user),
+                    )
+                    .returns::<()>()
+                    .try_invoke();
+            }
+        }
+
+        fn find_task<'a>(tasks: &'a mut [Task], title: &str) -> Result<&'a mut Task, Error> {
+            tasks
+                .iter_mut()
+                .find(|t| t.title == title)
+                .ok_or(Error::TaskNotFound)
+        }
+
+        /// Counts an add for the current block, failing once the cap is reached
+        fn check_rate_limit(&mut self, user: AccountId) -> Result<(), Error> {
+            if self.max_adds_per_block == 0 {
+                return Ok(());
+            }
+
+            let block = self.env().block_number();
+            let adds = match self.adds_in_block.get(user) {
+                Some((last_block, adds)) if last_block == block => adds,
+                _ => 0,
+            };
+            if adds >= self.max_adds_per_block {
+                return Err(Error::RateLimited);
+            }
+
+            self.adds_in_block.insert(user, &(block, adds + 1));
+            Ok(())
+        }
+
+        /// Adds to the lifetime count of tasks added to the list of a user
+        fn record_adds(&mut self, user: AccountId, count: u64) {
+            let added = self.added_total.get(user).unwrap_or(0);
+            self.added_total.insert(user, &(added + count));
+        }
+
+        /// Adds to the lifetime count of tasks removed from the list of a user
+        fn record_removals(&mut self, user: AccountId, count: u64) {
+            let removed = self.removed_total.get(user).unwrap_or(0);
+            self.removed_total.insert(user, &(removed + count));
+        }
+
+        /// Extends or restarts the completion streak of a user
+        fn record_completion(&mut self, user: AccountId) {
+            let today = self.env().block_timestamp() / DAY_MS;
+            let streak = match self.last_completion_day.get(user) {
+                Some(day) if day == today => return,
+                Some(day) if day + 1 == today => self.streak.get(user).unwrap_or_default() + 1,
+                _ => 1,
+            };
+
+            self.streak.insert(user, &streak);
+            self.last_completion_day.insert(user, &today);
+        }
+
+        fn parse_task_line(line: &str, separator: &str) -> Option<Task> {
+            let mut fields = line.splitn(3, separator);
+            let title = fields.next().filter(|title| !title.is_empty())?;
+            let state = match fields.next()? {
+                "Todo" => TaskState::Todo,
+                "Wip" => TaskState::Wip,
+                "Done" => TaskState::Done,
+                _ => return None,
+            };
+            let description = fields.next()?;
+
+            Some(Task::new(title.into(), description.into(), state))
+        }
+
+        /// Levenshtein distance between two strings, counted in chars
+        fn edit_distance(a: &str, b: &str) -> u32 {
+            let b: Vec<char> = b.chars().collect();
+            let mut previous: Vec<u32> = (0..=b.len() as u32).collect();
+
+            for (i, ca) in a.chars().enumerate() {
+                let mut current = Vec::with_capacity(b.len() + 1);
+                current.push(i as u32 + 1);
+                for (j, cb) in b.iter().enumerate() {
+                    let substitution = previous[j] + u32::from(ca != *cb);
+                    let deletion = previous[j + 1] + 1;
+                    let insertion = current[j] + 1;
+                    current.push(substitution.min(deletion).min(insertion));
+                }
+                previous = current;
+            }
+            previous[b.len()]
+        }
+
+        /// Emits an event unless events are turned off
+        fn emit<E>(&self, event: E)
+        where
+            E: Into<<Tasks as ink::reflect::ContractEventBase>::Type>,
+        {
+            if self.emit_events {
+                self.env().emit_event(event);
+            }
+        }
+    }
+
+    impl TaskQuery for Tasks {
+        #[ink(message)]
+        fn task_count(&self, user: AccountId) -> u32 {
+            self.get_task(user).len() as u32
+        }
+    }
+
+    impl Default for Tasks {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    /// Unit tests in Rust are normally defined within such a `#[cfg(test)]`
+    /// module and test functions are marked with a `#[test]` attribute.
+    /// The below code is technically just normal Rust code.
+    #[cfg(test)]
+    mod tests {
+        /// Imports all the definitions from the outer scope so we can use them here.
+        use super::*;
+
+        type Event = <Tasks as ::ink::reflect::ContractEventBase>::Type;
+
+        fn accounts() -> ink::env::test::DefaultAccounts<ink::env::DefaultEnvironment> {
+            ink::env::test::default_accounts::<ink::env::DefaultEnvironment>()
+        }
+
+        fn task(title: &str, state: TaskState) -> Task {
+            Task::new(title.to_string(), String::new(), state)
+        }
+
+        fn titles(tasks: &[Task]) -> Vec<&str> {
+            tasks.iter().map(|t| t.title.as_str()).collect()
+        }
+
+        #[ink::test]
+        fn replace_all_tasks_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("Old", TaskState::Todo)).unwrap();
+
+            let new_tasks = vec![task("A", TaskState::Todo), task("B", TaskState::Done)];
+            assert_eq!(tasks.replace_all_tasks(alice, new_tasks), Ok(()));
+            let list = tasks.get_task(alice);
+            assert_eq!(titles(&list), vec!["A", "B"]);
+            assert_eq!(list[1].state, TaskState::Done);
+            assert_eq!((list[0].id, list[1].id), (1, 2));
+        }
+
+        #[ink::test]
+        fn replace_all_tasks_validates_the_list() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("Old", TaskState::Todo)).unwrap();
+            let tagged = Task {
+                tags: (0..=MAX_TAGS_PER_TASK).map(|i| format!("tag{i}")).collect(),
+                ..task("A", TaskState::Todo)
+            };
+
+            assert_eq!(
+                tasks.replace_all_tasks(alice, vec![task("A\nB", TaskState::Todo)]),
+                Err(Error::InvalidTitle)
+            );
+            assert_eq!(
+                tasks.replace_all_tasks(alice, vec![tagged]),
+                Err(Error::TagLimitReached)
+            );
+            assert_eq!(tasks.set_max_wip(1), Ok(()));
+            assert_eq!(
+                tasks.replace_all_tasks(
+                    alice,
+                    vec![task("A", TaskState::Wip), task("B", TaskState::Wip)]
+                ),
+                Err(Error::WipLimitReached)
+            );
+            assert_eq!(tasks.set_max_bytes_per_user(8), Ok(()));
+            assert_eq!(
+                tasks.replace_all_tasks(alice, vec![task("A", TaskState::Todo)]),
+                Err(Error::QuotaExceeded)
+            );
+            assert_eq!(tasks.get_task(alice), vec![task("Old", TaskState::Todo)]);
+        }
+
+        #[ink::test]
+        fn replace_all_tasks_rejects_duplicate_titles() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("Old", TaskState::Todo)).unwrap();
+
+            let new_tasks = vec![task("A", TaskState::Todo), task("A", TaskState::Done)];
+            assert_eq!(
+                tasks.replace_all_tasks(alice, new_tasks),
+                Err(Error::DuplicateTitle)
+            );
+            assert_eq!(tasks.get_task(alice), vec![task("Old", TaskState::Todo)]);
+        }
+
+        #[ink::test]
+        fn grouped_by_state_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Done)).unwrap();
+            tasks.add_task(alice, task("B", TaskState::Todo)).unwrap();
+            tasks.add_task(alice, task("C", TaskState::Wip)).unwrap();
+            tasks.add_task(alice, task("D", TaskState::Todo)).unwrap();
+
+            let (todo, wip, done) = tasks.grouped_by_state(alice);
+            assert_eq!(titles(&todo), vec!["B", "D"]);
+            assert_eq!(titles(&wip), vec!["C"]);
+            assert_eq!(titles(&done), vec!["A"]);
+        }
+
+        #[ink::test]
+        fn tasks_created_between_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            for (title, timestamp) in [("A", 100), ("B", 200), ("C", 300), ("D", 400)] {
+                ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(timestamp);
+                tasks.add_task(alice, task(title, TaskState::Todo)).unwrap();
+            }
+
+            assert_eq!(tasks.tasks_created_between(alice, 200, 300), 2);
+            assert_eq!(tasks.tasks_created_between(alice, 150, 1000), 3);
+            assert_eq!(tasks.tasks_created_between(alice, 500, 1000), 0);
+        }
+
+        #[ink::test]
+        fn latest_task_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            assert_eq!(tasks.latest_task(alice), None);
+
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+            tasks.add_task(alice, task("B", TaskState::Todo)).unwrap();
+            tasks.add_task(alice, task("C", TaskState::Todo)).unwrap();
+            assert_eq!(tasks.latest_task(alice).unwrap().title, "C");
+        }
+
+        #[ink::test]
+        fn add_task_titled_defaults_to_todo() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks
+                .add_task_titled(alice, "A".to_string(), String::new())
+                .unwrap();
+            assert_eq!(tasks.get_task(alice), vec![task("A", TaskState::Todo)]);
+        }
+
+        #[ink::test]
+        fn add_task_titled_uses_default_state() {
+            let mut tasks = Tasks::with_default_state(TaskState::Wip);
+            let alice = accounts().alice;
+            tasks
+                .add_task_titled(alice, "A".to_string(), String::new())
+                .unwrap();
+            assert_eq!(tasks.get_task(alice), vec![task("A", TaskState::Wip)]);
+        }
+
+        #[ink::test]
+        fn flag_overdue_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            let due = |title, state, due| Task {
+                due: Some(due),
+                ..task(title, state)
+            };
+            tasks
+                .add_task(alice, due("A", TaskState::Todo, 100))
+                .unwrap();
+            tasks
+                .add_task(alice, due("B", TaskState::Wip, 200))
+                .unwrap();
+            tasks
+                .add_task(alice, due("C", TaskState::Done, 100))
+                .unwrap();
+            tasks
+                .add_task(alice, due("D", TaskState::Todo, 1000))
+                .unwrap();
+            tasks.add_task(alice, task("E", TaskState::Todo)).unwrap();
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(500);
+            let events_before = ink::env::test::recorded_events().count();
+            assert_eq!(tasks.flag_overdue(alice), Ok(2));
+            assert_eq!(ink::env::test::recorded_events().count() - events_before, 3);
+
+            assert_eq!(tasks.flag_overdue(alice), Ok(0));
+            assert_eq!(ink::env::test::recorded_events().count() - events_before, 3);
+        }
+
+        #[ink::test]
+        fn delegate_task_works() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            tasks
+                .add_task(accounts.alice, task("A", TaskState::Todo))
+                .unwrap();
+            tasks
+                .add_task(accounts.alice, task("B", TaskState::Wip))
+                .unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(tasks.delegate_task("B".to_string(), accounts.bob), Ok(()));
+            assert_eq!(titles(&tasks.get_task(accounts.alice)), vec!["A"]);
+            assert_eq!(titles(&tasks.get_task(accounts.bob)), vec!["B"]);
+            assert_eq!(tasks.get_task(accounts.bob)[0].state, TaskState::Wip);
+            assert_eq!(
+                tasks.delegate_task("B".to_string(), accounts.bob),
+                Err(Error::TaskNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn delegate_task_rejects_duplicate_at_destination() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            tasks
+                .add_task(accounts.alice, task("A", TaskState::Todo))
+                .unwrap();
+            tasks
+                .add_task(accounts.bob, task("A", TaskState::Done))
+                .unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(
+                tasks.delegate_task("A".to_string(), accounts.bob),
+                Err(Error::DuplicateTitle)
+            );
+            assert_eq!(tasks.get_task(accounts.alice)[0].state, TaskState::Todo);
+            assert_eq!(tasks.get_task(accounts.bob)[0].state, TaskState::Done);
+        }
+
+        #[ink::test]
+        fn delegate_task_respects_destination_limits() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            let big = Task {
+                description: "a".repeat(200),
+                ..task("Big", TaskState::Todo)
+            };
+            tasks
+                .add_task(accounts.alice, task("A", TaskState::Wip))
+                .unwrap();
+            tasks.add_task(accounts.alice, big).unwrap();
+            tasks
+                .add_task(accounts.bob, task("Call", TaskState::Wip))
+                .unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(tasks.set_max_wip(1), Ok(()));
+            assert_eq!(
+                tasks.delegate_task("A".to_string(), accounts.bob),
+                Err(Error::WipLimitReached)
+            );
+
+            let quota = scale::Encode::encoded_size(&tasks.get_task(accounts.alice));
+            assert_eq!(tasks.set_max_bytes_per_user(quota as u32), Ok(()));
+            assert_eq!(
+                tasks.delegate_task("Big".to_string(), accounts.bob),
+                Err(Error::QuotaExceeded)
+            );
+            assert_eq!(titles(&tasks.get_task(accounts.alice)), vec!["A", "Big"]);
+            assert_eq!(titles(&tasks.get_task(accounts.bob)), vec!["Call"]);
+        }
+
+        #[ink::test]
+        fn state_breakdown_works() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            tasks
+                .add_task(accounts.alice, task("A", TaskState::Todo))
+                .unwrap();
+            tasks
+                .add_task(accounts.alice, task("B", TaskState::Done))
+                .unwrap();
+            tasks
+                .add_task(accounts.bob, task("A", TaskState::Wip))
+                .unwrap();
+            tasks
+                .add_task(accounts.bob, task("B", TaskState::Wip))
+                .unwrap();
+            tasks
+                .add_task(accounts.bob, task("C", TaskState::Todo))
+                .unwrap();
+
+            assert_eq!(
+                tasks.state_breakdown(vec![accounts.alice, accounts.bob]),
+                Ok(vec![(accounts.alice, 1, 0, 1), (accounts.bob, 1, 2, 0)])
+            );
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                tasks.state_breakdown(vec![accounts.alice]),
+                Err(Error::NotOwner)
+            );
+        }
+
+        #[ink::test]
+        fn update_task_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+
+            assert_eq!(
+                tasks.update_task(alice, "A".to_string(), Some("desc".to_string()), None),
+                Ok(())
+            );
+            assert_eq!(tasks.get_task(alice)[0].description, "desc");
+            assert_eq!(tasks.get_task(alice)[0].state, TaskState::Todo);
+
+            assert_eq!(
+                tasks.update_task(alice, "A".to_string(), None, Some(TaskState::Wip)),
+                Ok(())
+            );
+            assert_eq!(tasks.get_task(alice)[0].description, "desc");
+            assert_eq!(tasks.get_task(alice)[0].state, TaskState::Wip);
+
+            assert_eq!(
+                tasks.update_task(
+                    alice,
+                    "A".to_string(),
+                    Some("other".to_string()),
+                    Some(TaskState::Done)
+                ),
+                Ok(())
+            );
+            assert_eq!(tasks.get_task(alice)[0].description, "other");
+            assert_eq!(tasks.get_task(alice)[0].state, TaskState::Done);
+        }
+
+        #[ink::test]
+        fn update_task_rejects_unknown_title() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            assert_eq!(
+                tasks.update_task(alice, "A".to_string(), None, Some(TaskState::Wip)),
+                Err(Error::TaskNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn for_team_registers_members() {
+            let accounts = accounts();
+            let tasks = Tasks::for_team(
+                accounts.alice,
+                vec![
+                    accounts.bob,
+                    accounts.charlie,
+                    accounts.bob,
+                    accounts.django,
+                ],
+            );
+
+            assert_eq!(
+                tasks.get_all_users(),
+                vec![accounts.bob, accounts.charlie, accounts.django]
+            );
+            assert_eq!(tasks.get_task(accounts.charlie), vec![]);
+        }
+
+        #[ink::test]
+        fn get_all_users_tracks_new_users() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            tasks
+                .add_task(accounts.alice, task("A", TaskState::Todo))
+                .unwrap();
+            tasks
+                .add_task(accounts.bob, task("A", TaskState::Todo))
+                .unwrap();
+            tasks
+                .add_task(accounts.alice, task("B", TaskState::Todo))
+                .unwrap();
+
+            assert_eq!(tasks.get_all_users(), vec![accounts.alice, accounts.bob]);
+        }
+
+        #[ink::test]
+        fn find_duplicate_titles_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            for title in ["A", "B", "A", "C", "B", "A"] {
+                tasks.add_task(alice, task(title, TaskState::Todo)).unwrap();
+            }
+
+            assert_eq!(
+                tasks.find_duplicate_titles(alice),
+                vec!["A".to_string(), "B".to_string()]
+            );
+        }
+
+        #[ink::test]
+        fn task_state_u8_round_trip() {
+            for state in [TaskState::Todo, TaskState::Wip, TaskState::Done] {
+                assert_eq!(TaskState::from_u8(state.as_u8()), Some(state));
+            }
+            assert_eq!(TaskState::from_u8(3), None);
+        }
+
+        #[ink::test]
+        fn task_count_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            assert_eq!(TaskQuery::task_count(&tasks, alice), 0);
+
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+            tasks.add_task(alice, task("B", TaskState::Done)).unwrap();
+            assert_eq!(TaskQuery::task_count(&tasks, alice), 2);
+        }
+
+        #[ink::test]
+        fn set_rewards_contract_is_owner_only() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                tasks.set_rewards_contract(Some(accounts.django)),
+                Err(Error::NotOwner)
+            );
+        }
+
+        #[ink::test]
+        fn rewards_are_not_called_before_done() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            assert_eq!(tasks.set_rewards_contract(Some(accounts.django)), Ok(()));
+            tasks
+                .add_task(accounts.alice, task("A", TaskState::Todo))
+                .unwrap();
+
+            assert_eq!(
+                tasks.update_task(accounts.alice, "A".to_string(), None, Some(TaskState::Wip)),
+                Ok(())
+            );
+        }
+
+        /// The off-chain environment cannot run cross-contract calls, so reaching
+        /// the call on a `Done` transition panics.
+        #[ink::test]
+        #[should_panic(expected = "off-chain environment does not support contract invocation")]
+        fn rewards_are_called_on_done() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            assert_eq!(tasks.set_rewards_contract(Some(accounts.django)), Ok(()));
+            tasks
+                .add_task(accounts.alice, task("A", TaskState::Wip))
+                .unwrap();
+
+            let _ = tasks.update_task(accounts.alice, "A".to_string(), None, Some(TaskState::Done));
+        }
+
+        #[ink::test]
+        fn has_completed_at_least_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Done)).unwrap();
+            tasks.add_task(alice, task("B", TaskState::Wip)).unwrap();
+            tasks.add_task(alice, task("C", TaskState::Done)).unwrap();
+
+            assert!(tasks.has_completed_at_least(alice, 1));
+            assert!(tasks.has_completed_at_least(alice, 2));
+            assert!(!tasks.has_completed_at_least(alice, 3));
+        }
+
+        #[ink::test]
+        fn visible_tasks_includes_shared_lists() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            tasks
+                .add_task(accounts.alice, task("A", TaskState::Todo))
+                .unwrap();
+            tasks
+                .add_task(accounts.bob, task("B", TaskState::Wip))
+                .unwrap();
+            tasks
+                .add_task(accounts.charlie, task("C", TaskState::Done))
+                .unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            tasks.share_list(accounts.alice, true);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            let visible: Vec<(AccountId, String)> = tasks
+                .visible_tasks()
+                .into_iter()
+                .map(|(owner, t)| (owner, t.title))
+                .collect();
+            assert_eq!(
+                visible,
+                vec![
+                    (accounts.alice, "A".to_string()),
+                    (accounts.bob, "B".to_string())
+                ]
+            );
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            tasks.share_list(accounts.alice, false);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(tasks.visible_tasks().len(), 1);
+        }
+
+        #[ink::test]
+        fn snapshot_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+
+            assert_eq!(
+                tasks.snapshot(alice),
+                (
+                    ink::env::block_number::<ink::env::DefaultEnvironment>() as u64,
+                    vec![task("A", TaskState::Todo)]
+                )
+            );
+            assert_eq!(tasks.snapshot(alice).0, 2);
+        }
+
+        #[ink::test]
+        fn restore_snapshot_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            let (block, snapshot) = tasks.snapshot(alice);
+
+            tasks.replace_all_tasks(alice, vec![]).unwrap();
+            assert_eq!(tasks.restore_snapshot(alice, block, snapshot), Ok(()));
+            assert_eq!(tasks.get_task(alice), vec![task("A", TaskState::Todo)]);
+        }
+
+        #[ink::test]
+        fn restore_snapshot_rejects_stale_snapshot() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+            let (block, snapshot) = tasks.snapshot(alice);
+
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            tasks.add_task(alice, task("B", TaskState::Todo)).unwrap();
+            assert_eq!(
+                tasks.restore_snapshot(alice, block, snapshot),
+                Err(Error::StaleSnapshot)
+            );
+            assert_eq!(tasks.get_task(alice).len(), 2);
+        }
+
+        #[ink::test]
+        fn restore_snapshot_rejects_invalid_ids() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+            tasks.add_task(alice, task("B", TaskState::Todo)).unwrap();
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            let (block, mut snapshot) = tasks.snapshot(alice);
+
+            snapshot[1].id = snapshot[0].id;
+            assert_eq!(
+                tasks.restore_snapshot(alice, block, snapshot.clone()),
+                Err(Error::InvalidId)
+            );
+            snapshot[1].id = 100;
+            assert_eq!(
+                tasks.restore_snapshot(alice, block, snapshot.clone()),
+                Err(Error::InvalidId)
+            );
+            snapshot[1].title = "A".to_string();
+            assert_eq!(
+                tasks.restore_snapshot(alice, block, snapshot),
+                Err(Error::DuplicateTitle)
+            );
+            assert_eq!(titles(&tasks.get_task(alice)), vec!["A", "B"]);
+        }
+
+        #[ink::test]
+        fn tag_histogram_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            let tagged = |title, tags: &[&str]| Task {
+                tags: tags.iter().map(|t| t.to_string()).collect(),
+                ..task(title, TaskState::Todo)
+            };
+            tasks
+                .add_task(alice, tagged("A", &["work", "urgent"]))
+                .unwrap();
+            tasks.add_task(alice, tagged("B", &["home"])).unwrap();
+            tasks.add_task(alice, tagged("C", &["work"])).unwrap();
+            tasks.add_task(alice, tagged("D", &[])).unwrap();
+
+            assert_eq!(
+                tasks.tag_histogram(alice),
+                vec![
+                    ("work".to_string(), 2),
+                    ("urgent".to_string(), 1),
+                    ("home".to_string(), 1),
+                ]
+            );
+        }
+
+        #[ink::test]
+        fn remove_tag_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+            assert_eq!(
+                tasks.add_tag(alice, "A".to_string(), "work".to_string()),
+                Ok(())
+            );
+            assert_eq!(
+                tasks.add_tag(alice, "A".to_string(), "home".to_string()),
+                Ok(())
+            );
+
+            assert_eq!(
+                tasks.remove_tag(alice, "A".to_string(), "work".to_string()),
+                Ok(())
+            );
+            assert_eq!(tasks.get_task(alice)[0].tags, vec!["home".to_string()]);
+        }
+
+        #[ink::test]
+        fn remove_tag_rejects_missing_tag_or_task() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+
+            assert_eq!(
+                tasks.remove_tag(alice, "A".to_string(), "work".to_string()),
+                Err(Error::TagNotFound)
+            );
+            assert_eq!(
+                tasks.remove_tag(alice, "B".to_string(), "work".to_string()),
+                Err(Error::TaskNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn emit_summary_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+            tasks.add_task(alice, task("B", TaskState::Done)).unwrap();
+            tasks.add_task(alice, task("C", TaskState::Done)).unwrap();
+
+            tasks.emit_summary(alice);
+            let event = ink::env::test::recorded_events().last().unwrap();
+            let decoded = <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap();
+            match decoded {
+                Event::ListSummary(summary) => {
+                    assert_eq!(summary.user, alice);
+                    assert_eq!((summary.todo, summary.wip, summary.done), (1, 0, 2));
+                }
+                _ => panic!("expected a ListSummary event"),
+            }
+        }
+
+        #[ink::test]
+        fn task_exists_works() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            tasks
+                .add_task(accounts.alice, task("A", TaskState::Todo))
+                .unwrap();
+
+            assert!(tasks.task_exists(accounts.alice, "A".to_string()));
+            assert!(!tasks.task_exists(accounts.alice, "B".to_string()));
+            assert!(!tasks.task_exists(accounts.bob, "A".to_string()));
+        }
+
+        #[ink::test]
+        fn add_task_is_rate_limited_per_block() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            assert_eq!(tasks.set_max_adds_per_block(2), Ok(()));
+
+            assert_eq!(tasks.add_task(alice, task("A", TaskState::Todo)), Ok(()));
+            assert_eq!(tasks.add_task(alice, task("B", TaskState::Todo)), Ok(()));
+            assert_eq!(
+                tasks.add_task(alice, task("C", TaskState::Todo)),
+                Err(Error::RateLimited)
+            );
+
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            assert_eq!(tasks.add_task(alice, task("C", TaskState::Todo)), Ok(()));
+            assert_eq!(tasks.get_task(alice).len(), 3);
+        }
+
+        #[ink::test]
+        fn get_tasks_with_ids_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            for title in ["A", "B", "C"] {
+                tasks.add_task(alice, task(title, TaskState::Todo)).unwrap();
+            }
+            tasks.remove_task(alice, "B".to_string()).unwrap();
+            tasks.add_task(alice, task("D", TaskState::Todo)).unwrap();
+
+            let ids: Vec<(u64, String)> = tasks
+                .get_tasks_with_ids(alice)
+                .into_iter()
+                .map(|(id, t)| (id, t.title))
+                .collect();
+            assert_eq!(
+                ids,
+                vec![
+                    (0, "A".to_string()),
+                    (2, "C".to_string()),
+                    (3, "D".to_string())
+                ]
+            );
+        }
+
+        #[ink::test]
+        fn batch_update_states_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+            tasks.add_task(alice, task("B", TaskState::Todo)).unwrap();
+
+            assert_eq!(
+                tasks.batch_update_states(
+                    alice,
+                    vec![
+                        ("A".to_string(), TaskState::Wip),
+                        ("B".to_string(), TaskState::Done)
+                    ]
+                ),
+                Ok(())
+            );
+            let states: Vec<TaskState> = tasks.get_task(alice).iter().map(|t| t.state).collect();
+            assert_eq!(states, vec![TaskState::Wip, TaskState::Done]);
+        }
+
+        #[ink::test]
+        fn batch_update_states_is_all_or_nothing() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+
+            assert_eq!(
+                tasks.batch_update_states(
+                    alice,
+                    vec![
+                        ("A".to_string(), TaskState::Wip),
+                        ("B".to_string(), TaskState::Done)
+                    ]
+                ),
+                Err(Error::TaskNotFound)
+            );
+            assert_eq!(tasks.get_task(alice)[0].state, TaskState::Todo);
+        }
+
+        #[ink::test]
+        fn get_tasks_sorted_by_title_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            for title in ["B", "C", "A"] {
+                tasks.add_task(alice, task(title, TaskState::Todo)).unwrap();
+            }
+
+            assert_eq!(
+                titles(&tasks.get_tasks_sorted_by_title(alice, true)),
+                vec!["A", "B", "C"]
+            );
+            assert_eq!(
+                titles(&tasks.get_tasks_sorted_by_title(alice, false)),
+                vec!["C", "B", "A"]
+            );
+            assert_eq!(titles(&tasks.get_task(alice)), vec!["B", "C", "A"]);
+        }
+
+        #[ink::test]
+        fn streak_grows_and_resets() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            let complete_on_day = |tasks: &mut Tasks, title: &str, day: u64| {
+                ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(day * DAY_MS);
+                tasks.add_task(alice, task(title, TaskState::Todo)).unwrap();
+                tasks
+                    .update_task(alice, title.to_string(), None, Some(TaskState::Done))
+                    .unwrap();
+            };
+            assert_eq!(tasks.get_streak(alice), 0);
+
+            complete_on_day(&mut tasks, "A", 1);
+            complete_on_day(&mut tasks, "B", 1);
+            assert_eq!(tasks.get_streak(alice), 1);
+            complete_on_day(&mut tasks, "C", 2);
+            complete_on_day(&mut tasks, "D", 3);
+            assert_eq!(tasks.get_streak(alice), 3);
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(5 * DAY_MS);
+            assert_eq!(tasks.get_streak(alice), 0);
+            complete_on_day(&mut tasks, "E", 5);
+            assert_eq!(tasks.get_streak(alice), 1);
+        }
+
+        #[ink::test]
+        fn changed_since_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            let (block, _) = tasks.snapshot(alice);
+            assert!(!tasks.changed_since(alice, block));
+
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            assert!(!tasks.changed_since(alice, block));
+            tasks.add_task(alice, task("B", TaskState::Todo)).unwrap();
+            assert!(tasks.changed_since(alice, block));
+        }
+
+        #[ink::test]
+        fn freeze_user_blocks_mutations() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            tasks
+                .add_task(accounts.bob, task("A", TaskState::Todo))
+                .unwrap();
+            assert_eq!(tasks.set_manager(accounts.charlie, true), Ok(()));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(tasks.freeze_user(accounts.bob, true), Ok(()));
+            assert_eq!(
+                tasks.add_task(accounts.bob, task("B", TaskState::Todo)),
+                Err(Error::UserFrozen)
+            );
+            assert_eq!(
+                tasks.remove_task(accounts.bob, "A".to_string()),
+                Err(Error::UserFrozen)
+            );
+            assert_eq!(
+                tasks.update_task(accounts.bob, "A".to_string(), None, Some(TaskState::Done)),
+                Err(Error::UserFrozen)
+            );
+            let bob = accounts.bob;
+            let frozen = Err(Error::UserFrozen);
+            assert_eq!(tasks.flag_overdue(bob), frozen);
+            assert_eq!(tasks.auto_archive_done_older_than(bob, 0), frozen);
+            assert_eq!(
+                tasks.set_priority_for_state(bob, TaskState::Todo, Priority::High),
+                frozen
+            );
+            assert_eq!(tasks.reset_all_to_todo(bob), frozen);
+            assert_eq!(tasks.schedule_all(bob, DAY_MS), frozen);
+            assert_eq!(tasks.prune_older_than(bob, u64::MAX), frozen);
+            assert_eq!(tasks.complete_ids(bob, vec![0]), frozen);
+            assert_eq!(tasks.sort_by_due(bob), Err(Error::UserFrozen));
+            assert_eq!(titles(&tasks.get_task(accounts.bob)), vec!["A"]);
+
+            assert_eq!(tasks.freeze_user(accounts.bob, false), Ok(()));
+            assert_eq!(
+                tasks.add_task(accounts.bob, task("B", TaskState::Todo)),
+                Ok(())
+            );
+            assert_eq!(tasks.remove_task(accounts.bob, "A".to_string()), Ok(()));
+            assert_eq!(titles(&tasks.get_task(accounts.bob)), vec!["B"]);
+        }
+
+        #[ink::test]
+        fn freeze_user_is_owner_or_manager_only() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                tasks.freeze_user(accounts.alice, true),
+                Err(Error::NotAuthorized)
+            );
+            assert_eq!(tasks.set_manager(accounts.bob, true), Err(Error::NotOwner));
+        }
+
+        #[ink::test]
+        fn done_timeline_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            for title in ["A", "B", "C"] {
+                tasks.add_task(alice, task(title, TaskState::Todo)).unwrap();
+            }
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(100);
+            tasks
+                .update_task(alice, "B".to_string(), None, Some(TaskState::Done))
+                .unwrap();
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(200);
+            tasks
+                .update_task(alice, "A".to_string(), None, Some(TaskState::Done))
+                .unwrap();
+
+            assert_eq!(
+                tasks.done_timeline(alice),
+                vec![("B".to_string(), 100), ("A".to_string(), 200)]
+            );
+        }
+
+        #[ink::test]
+        fn empty_descriptions_allowed_by_default() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            assert_eq!(tasks.add_task(alice, task("A", TaskState::Todo)), Ok(()));
+        }
+
+        #[ink::test]
+        fn empty_descriptions_can_be_rejected() {
+            let mut tasks = Tasks::with_description_policy(false);
+            let alice = accounts().alice;
+            assert_eq!(
+                tasks.add_task(alice, task("A", TaskState::Todo)),
+                Err(Error::InvalidDescription)
+            );
+
+            let described = Task {
+                description: "desc".to_string(),
+                ..task("A", TaskState::Todo)
+            };
+            assert_eq!(tasks.add_task(alice, described), Ok(()));
+            assert_eq!(
+                tasks.update_task(alice, "A".to_string(), Some(String::new()), None),
+                Err(Error::InvalidDescription)
+            );
+            assert_eq!(tasks.get_task(alice)[0].description, "desc");
+        }
+
+        #[ink::test]
+        fn get_state_paged_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            for (title, state) in [
+                ("A", TaskState::Done),
+                ("B", TaskState::Todo),
+                ("C", TaskState::Done),
+                ("D", TaskState::Done),
+            ] {
+                tasks.add_task(alice, task(title, state)).unwrap();
+            }
+
+            assert_eq!(
+                titles(&tasks.get_state_paged(alice, TaskState::Done, 1, 5)),
+                vec!["C", "D"]
+            );
+            assert_eq!(
+                titles(&tasks.get_state_paged(alice, TaskState::Done, 0, 2)),
+                vec!["A", "C"]
+            );
+            assert_eq!(tasks.get_state_paged(alice, TaskState::Done, 3, 5), vec![]);
+        }
+
+        #[ink::test]
+        fn terminate_is_owner_only() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(tasks.terminate(), Err(Error::NotOwner));
+        }
+
+        #[ink::test]
+        fn terminate_works() {
+            let accounts = accounts();
+            let contract = ink::env::test::callee::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(contract, 100);
+            let mut tasks = Tasks::new();
+
+            let should_terminate = move || tasks.terminate().unwrap();
+            ink::env::test::assert_contract_termination::<ink::env::DefaultEnvironment, _>(
+                should_terminate,
+                accounts.alice,
+                100,
+            );
+        }
+
+        #[ink::test]
+        fn overdue_ratio_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            let due = |title, state, due| Task {
+                due: Some(due),
+                ..task(title, state)
+            };
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(500);
+            assert_eq!(tasks.overdue_ratio(alice), 0);
+
+            tasks
+                .add_task(alice, due("A", TaskState::Done, 100))
+                .unwrap();
+            assert_eq!(tasks.overdue_ratio(alice), 0);
+
+            tasks
+                .add_task(alice, due("B", TaskState::Todo, 100))
+                .unwrap();
+            tasks
+                .add_task(alice, due("C", TaskState::Wip, 200))
+                .unwrap();
+            assert_eq!(tasks.overdue_ratio(alice), 100);
+
+            tasks
+                .add_task(alice, due("D", TaskState::Todo, 1000))
+                .unwrap();
+            assert_eq!(tasks.overdue_ratio(alice), 66);
+        }
+
+        #[ink::test]
+        fn import_from_text_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            let lines = vec!["A|Todo|first".to_string(), "B|Done|".to_string()];
+
+            assert_eq!(tasks.import_from_text(alice, lines, "|".to_string()), Ok(2));
+            let imported = tasks.get_task(alice);
+            assert_eq!(titles(&imported), vec!["A", "B"]);
+            assert_eq!(imported[0].description, "first");
+            assert_eq!(imported[1].state, TaskState::Done);
+        }
+
+        #[ink::test]
+        fn tasks_added_as_done_get_a_completion_time() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(100);
+            let lines = vec!["A|Done|x".to_string()];
+            tasks
+                .import_from_text(alice, lines, "|".to_string())
+                .unwrap();
+            tasks.add_task(alice, task("B", TaskState::Done)).unwrap();
+            assert_eq!(
+                tasks.done_timeline(alice),
+                vec![("A".to_string(), 100), ("B".to_string(), 100)]
+            );
+
+            tasks
+                .replace_all_tasks(alice, vec![task("C", TaskState::Done)])
+                .unwrap();
+            assert_eq!(tasks.recent_done(alice, 1)[0].completed_at, Some(100));
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(200);
+            assert_eq!(tasks.auto_archive_done_older_than(alice, 50), Ok(1));
+        }
+
+        #[ink::test]
+        fn import_from_text_rejects_malformed_lines() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            let lines = vec!["A|Todo|first".to_string(), "B|Later|second".to_string()];
+
+            assert_eq!(
+                tasks.import_from_text(alice, lines, "|".to_string()),
+                Err(Error::ParseError(1))
+            );
+            assert_eq!(
+                tasks.import_from_text(alice, vec!["C".to_string()], "|".to_string()),
+                Err(Error::ParseError(0))
+            );
+            assert_eq!(tasks.get_task(alice), vec![]);
+        }
+
+        #[ink::test]
+        fn get_open_tasks_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            for (title, state) in [
+                ("A", TaskState::Done),
+                ("B", TaskState::Todo),
+                ("C", TaskState::Wip),
+                ("D", TaskState::Done),
+            ] {
+                tasks.add_task(alice, task(title, state)).unwrap();
+            }
+
+            assert_eq!(titles(&tasks.get_open_tasks(alice)), vec!["B", "C"]);
+        }
+
+        #[ink::test]
+        fn update_my_task_state_is_caller_scoped() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            tasks
+                .add_task(accounts.alice, task("A", TaskState::Todo))
+                .unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(
+                tasks.update_my_task_state("A".to_string(), TaskState::Wip),
+                Ok(())
+            );
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                tasks.update_my_task_state("A".to_string(), TaskState::Done),
+                Err(Error::TaskNotFound)
+            );
+            assert_eq!(tasks.get_task(accounts.alice)[0].state, TaskState::Wip);
+        }
+
+        #[ink::test]
+        fn count_by_state_matches_scan() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            assert_eq!(tasks.count_by_state(alice), (0, 0, 0));
+
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+            tasks.add_task(alice, task("B", TaskState::Todo)).unwrap();
+            tasks.add_task(alice, task("C", TaskState::Wip)).unwrap();
+            assert_eq!(tasks.count_by_state(alice), (2, 1, 0));
+
+            tasks
+                .update_task(alice, "A".to_string(), None, Some(TaskState::Done))
+                .unwrap();
+            tasks.remove_task(alice, "C".to_string()).unwrap();
+            assert_eq!(tasks.count_by_state(alice), (1, 0, 1));
+            assert_eq!(
+                tasks.count_by_state(alice),
+                tasks.count_by_state_scan(alice)
+            );
+
+            tasks
+                .batch_update_states(alice, vec![("B".to_string(), TaskState::Wip)])
+                .unwrap();
+            assert_eq!(tasks.count_by_state(alice), (0, 1, 1));
+            assert_eq!(
+                tasks.count_by_state(alice),
+                tasks.count_by_state_scan(alice)
+            );
+        }
+
+        #[ink::test]
+        fn tag_tasks_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            for title in ["A", "B", "C"] {
+                tasks.add_task(alice, task(title, TaskState::Todo)).unwrap();
+            }
+            tasks
+                .add_tag(alice, "B".to_string(), "work".to_string())
+                .unwrap();
+
+            let titles = vec!["A".to_string(), "B".to_string(), "D".to_string()];
+            assert_eq!(tasks.tag_tasks(alice, titles, "work".to_string()), Ok(1));
+            let tagged: Vec<bool> = tasks
+                .get_task(alice)
+                .iter()
+                .map(|t| t.tags.contains(&"work".to_string()))
+                .collect();
+            assert_eq!(tagged, vec![true, true, false]);
+        }
+
+        #[ink::test]
+        fn add_tasks_emits_one_event_per_task() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            let batch = vec![task("A", TaskState::Todo), task("B", TaskState::Todo)];
+
+            assert_eq!(tasks.add_tasks(alice, batch), Ok(2));
+            assert_eq!(ink::env::test::recorded_events().count(), 3);
+            assert_eq!(titles(&tasks.get_task(alice)), vec!["A", "B"]);
+        }
+
+        #[ink::test]
+        fn add_tasks_can_be_undone() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("Old", TaskState::Todo)).unwrap();
+            let batch = vec![
+                task("A", TaskState::Todo),
+                task("B", TaskState::Todo),
+                task("C", TaskState::Todo),
+            ];
+
+            assert_eq!(tasks.add_tasks(alice, batch), Ok(3));
+            assert_eq!(tasks.undo(alice), Ok(()));
+            assert_eq!(titles(&tasks.get_task(alice)), vec!["Old"]);
+        }
+
+        #[ink::test]
+        fn add_tasks_can_aggregate_events() {
+            let mut tasks = Tasks::with_batch_events(true);
+            let alice = accounts().alice;
+            let batch = vec![task("A", TaskState::Todo), task("B", TaskState::Todo)];
+
+            assert_eq!(tasks.add_tasks(alice, batch), Ok(2));
+            let events: Vec<_> = ink::env::test::recorded_events().collect();
+            assert_eq!(events.len(), 2);
+            let decoded = <Event as scale::Decode>::decode(&mut &events[1].data[..]).unwrap();
+            match decoded {
+                Event::BulkAdded(bulk) => assert_eq!((bulk.user, bulk.count), (alice, 2)),
+                _ => panic!("expected a BulkAdded event"),
+            }
+        }
+
+        #[ink::test]
+        fn list_header_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            assert_eq!(tasks.list_header(alice), (0, 0, 0));
+
+            tasks.add_task(alice, task("A", TaskState::Done)).unwrap();
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            tasks.add_task(alice, task("B", TaskState::Todo)).unwrap();
+            tasks.add_task(alice, task("C", TaskState::Wip)).unwrap();
+            tasks.add_task(alice, task("D", TaskState::Todo)).unwrap();
+
+            assert_eq!(tasks.list_header(alice), (4, 25, 1));
+        }
+
+        #[ink::test]
+        fn undo_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            assert_eq!(tasks.undo(alice), Err(Error::NothingToUndo));
+
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+            assert_eq!(tasks.undo(alice), Ok(()));
+            assert_eq!(tasks.get_task(alice), vec![]);
+            assert_eq!(tasks.undo(alice), Err(Error::NothingToUndo));
+        }
+
+        #[ink::test]
+        fn weighted_progress_works() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            let prioritized = |title, state, priority| Task {
+                priority,
+                ..task(title, state)
+            };
+            assert_eq!(tasks.weighted_progress(accounts.alice), 0);
+
+            for user in [accounts.alice, accounts.bob] {
+                tasks
+                    .add_task(user, prioritized("High", TaskState::Todo, Priority::High))
+                    .unwrap();
+                tasks
+                    .add_task(user, prioritized("Low", TaskState::Todo, Priority::Low))
+                    .unwrap();
+            }
+            assert_eq!(tasks.weighted_progress(accounts.alice), 0);
+
+            tasks
+                .update_task(
+                    accounts.alice,
+                    "High".to_string(),
+                    None,
+                    Some(TaskState::Done),
+                )
+                .unwrap();
+            tasks
+                .update_task(accounts.bob, "Low".to_string(), None, Some(TaskState::Done))
+                .unwrap();
+            assert_eq!(tasks.weighted_progress(accounts.alice), 75);
+            assert_eq!(tasks.weighted_progress(accounts.bob), 25);
+        }
+
+        #[ink::test]
+        fn float_state_to_top_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            for (title, state) in [
+                ("A", TaskState::Todo),
+                ("B", TaskState::Wip),
+                ("C", TaskState::Done),
+                ("D", TaskState::Wip),
+                ("E", TaskState::Todo),
+            ] {
+                tasks.add_task(alice, task(title, state)).unwrap();
+            }
+
+            assert_eq!(tasks.float_state_to_top(alice, TaskState::Wip), Ok(()));
+            assert_eq!(
+                titles(&tasks.get_task(alice)),
+                vec!["B", "D", "A", "C", "E"]
+            );
+        }
+
+        #[ink::test]
+        fn recent_done_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            for title in ["A", "B", "C"] {
+                tasks.add_task(alice, task(title, TaskState::Todo)).unwrap();
+            }
+            assert_eq!(tasks.recent_done(alice, 2), vec![]);
+
+            for (title, now) in [("B", 100), ("A", 200), ("C", 300)] {
+                ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(now);
+                tasks
+                    .update_task(alice, title.to_string(), None, Some(TaskState::Done))
+                    .unwrap();
+            }
+
+            assert_eq!(titles(&tasks.recent_done(alice, 2)), vec!["C", "A"]);
+            assert_eq!(titles(&tasks.recent_done(alice, 5)), vec!["C", "A", "B"]);
+        }
+
+        #[ink::test]
+        fn control_characters_in_titles_are_rejected() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+
+            assert_eq!(
+                tasks.add_task(alice, task("A\nB", TaskState::Todo)),
+                Err(Error::InvalidTitle)
+            );
+            assert_eq!(tasks.add_task(alice, task("A B", TaskState::Todo)), Ok(()));
+
+            assert_eq!(
+                tasks.rename_task(alice, "A B".to_string(), "A\0B".to_string()),
+                Err(Error::InvalidTitle)
+            );
+            assert_eq!(
+                tasks.rename_task(alice, "A B".to_string(), "C".to_string()),
+                Ok(())
+            );
+            assert_eq!(titles(&tasks.get_task(alice)), vec!["C"]);
+        }
+
+        #[ink::test]
+        fn byte_quota_works() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            let fill = |tasks: &mut Tasks, user| {
+                tasks.add_task(user, task("A", TaskState::Todo))?;
+                tasks.add_task(user, task("A much longer title", TaskState::Todo))
+            };
+            assert_eq!(tasks.estimated_storage_bytes(accounts.alice), 1);
+
+            fill(&mut tasks, accounts.alice).unwrap();
+            let quota = tasks.estimated_storage_bytes(accounts.alice);
+            assert_eq!(tasks.set_max_bytes_per_user(quota), Ok(()));
+
+            assert_eq!(fill(&mut tasks, accounts.bob), Ok(()));
+            assert_eq!(tasks.estimated_storage_bytes(accounts.bob), quota);
+            assert_eq!(
+                tasks.add_task(accounts.bob, task("B", TaskState::Todo)),
+                Err(Error::QuotaExceeded)
+            );
+            assert_eq!(tasks.get_task(accounts.bob).len(), 2);
+        }
+
+        #[ink::test]
+        fn complete_and_sink_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            for title in ["A", "B", "C"] {
+                tasks.add_task(alice, task(title, TaskState::Todo)).unwrap();
+            }
+
+            assert_eq!(
+                tasks.complete_and_sink(alice, "D".to_string()),
+                Err(Error::TaskNotFound)
+            );
+            assert_eq!(tasks.complete_and_sink(alice, "A".to_string()), Ok(()));
+
+            let list = tasks.get_task(alice);
+            assert_eq!(titles(&list), vec!["B", "C", "A"]);
+            assert_eq!(list[2].state, TaskState::Done);
+        }
+
+        #[ink::test]
+        fn scheduled_tasks_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+            tasks
+                .add_task(
+                    alice,
+                    Task {
+                        due: Some(100),
+                        ..task("B", TaskState::Todo)
+                    },
+                )
+                .unwrap();
+            tasks.add_task(alice, task("C", TaskState::Wip)).unwrap();
+            tasks
+                .add_task(
+                    alice,
+                    Task {
+                        due: Some(50),
+                        ..task("D", TaskState::Done)
+                    },
+                )
+                .unwrap();
+
+            assert_eq!(titles(&tasks.scheduled_tasks(alice)), vec!["B", "D"]);
+        }
+
+        #[ink::test]
+        fn auto_archive_done_older_than_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            for title in ["A", "B", "C"] {
+                tasks.add_task(alice, task(title, TaskState::Todo)).unwrap();
+            }
+            for (title, now) in [("A", 100), ("B", 1_000)] {
+                ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(now);
+                tasks
+                    .update_task(alice, title.to_string(), None, Some(TaskState::Done))
+                    .unwrap();
+            }
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1_500);
+            assert_eq!(tasks.auto_archive_done_older_than(alice, 1_000), Ok(1));
+            assert_eq!(titles(&tasks.get_task(alice)), vec!["B", "C"]);
+            assert_eq!(titles(&tasks.get_archived(alice)), vec!["A"]);
+            assert_eq!(tasks.undo(alice), Err(Error::NothingToUndo));
+
+            assert_eq!(tasks.auto_archive_done_older_than(alice, 1_000), Ok(0));
+        }
+
+        #[ink::test]
+        fn find_similar_titles_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            for title in ["Groceries", "Laundry", "Gardening"] {
+                tasks.add_task(alice, task(title, TaskState::Todo)).unwrap();
+            }
+
+            assert_eq!(
+                tasks.find_similar_titles(alice, "Groceriez".to_string(), 0),
+                Vec::<String>::new()
+            );
+            assert_eq!(
+                tasks.find_similar_titles(alice, "Groceriez".to_string(), 1),
+                vec!["Groceries".to_string()]
+            );
+            assert_eq!(
+                tasks.find_similar_titles(alice, "Laundry".to_string(), 0),
+                vec!["Laundry".to_string()]
+            );
+        }
+
+        #[ink::test]
+        fn count_where_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            let due = |title, state| Task {
+                due: Some(100),
+                ..task(title, state)
+            };
+            tasks.add_task(alice, due("A", TaskState::Todo)).unwrap();
+            tasks.add_task(alice, task("B", TaskState::Todo)).unwrap();
+            tasks.add_task(alice, due("C", TaskState::Wip)).unwrap();
+            tasks.add_task(alice, task("D", TaskState::Done)).unwrap();
+
+            assert_eq!(tasks.count_where(alice, None, false), 4);
+            assert_eq!(tasks.count_where(alice, None, true), 2);
+            assert_eq!(tasks.count_where(alice, Some(TaskState::Todo), false), 2);
+            assert_eq!(tasks.count_where(alice, Some(TaskState::Todo), true), 1);
+            assert_eq!(tasks.count_where(alice, Some(TaskState::Done), true), 0);
+        }
+
+        #[ink::test]
+        fn get_page_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            for title in ["A", "B", "C", "D", "E"] {
+                tasks.add_task(alice, task(title, TaskState::Todo)).unwrap();
+            }
+
+            let (page, total) = tasks.get_page(alice, 2, 2);
+            assert_eq!(titles(&page), vec!["C", "D"]);
+            assert_eq!(total, 5);
+
+            let (page, total) = tasks.get_page(alice, 4, 2);
+            assert_eq!(titles(&page), vec!["E"]);
+            assert_eq!(total, 5);
+        }
+
+        #[ink::test]
+        fn user_wip_limit_overrides_global() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            assert_eq!(tasks.set_max_wip(2), Ok(()));
+            assert_eq!(tasks.set_user_wip_limit(accounts.alice, 1), Ok(()));
+
+            for user in [accounts.alice, accounts.bob] {
+                tasks.add_task(user, task("A", TaskState::Wip)).unwrap();
+                tasks.add_task(user, task("B", TaskState::Todo)).unwrap();
+            }
+            let start = |tasks: &mut Tasks, user| {
+                tasks.update_task(user, "B".to_string(), None, Some(TaskState::Wip))
+            };
+
+            assert_eq!(
+                start(&mut tasks, accounts.alice),
+                Err(Error::WipLimitReached)
+            );
+            assert_eq!(
+                tasks.add_task(accounts.alice, task("C", TaskState::Wip)),
+                Err(Error::WipLimitReached)
+            );
+            assert_eq!(start(&mut tasks, accounts.bob), Ok(()));
+            assert_eq!(
+                tasks.add_task(accounts.bob, task("C", TaskState::Wip)),
+                Err(Error::WipLimitReached)
+            );
+        }
+
+        #[ink::test]
+        fn set_user_wip_limit_is_owner_or_manager_only() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                tasks.set_user_wip_limit(accounts.bob, 5),
+                Err(Error::NotAuthorized)
+            );
+        }
+
+        #[ink::test]
+        fn users_with_at_least_works() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            for (user, count) in [
+                (accounts.alice, 1),
+                (accounts.bob, 3),
+                (accounts.charlie, 2),
+            ] {
+                for title in ["A", "B", "C"].into_iter().take(count) {
+                    tasks.add_task(user, task(title, TaskState::Todo)).unwrap();
+                }
+            }
+
+            assert_eq!(
+                tasks.users_with_at_least(2),
+                Ok(vec![accounts.bob, accounts.charlie])
+            );
+            assert_eq!(tasks.users_with_at_least(4), Ok(vec![]));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(tasks.users_with_at_least(0), Err(Error::NotOwner));
+        }
+
+        #[ink::test]
+        fn state_name_works() {
+            let tasks = Tasks::new();
+            assert_eq!(tasks.state_name(TaskState::Todo), "Todo");
+            assert_eq!(tasks.state_name(TaskState::Wip), "Wip");
+            assert_eq!(tasks.state_name(TaskState::Done), "Done");
+        }
+
+        #[ink::test]
+        fn relocate_task_works() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(100);
+            tasks
+                .add_task(accounts.bob, task("A", TaskState::Todo))
+                .unwrap();
+            tasks
+                .add_tag(accounts.bob, "A".to_string(), "home".to_string())
+                .unwrap();
+            let moved = tasks.get_task(accounts.bob)[0].clone();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                tasks.relocate_task(accounts.bob, accounts.charlie, moved.id + 1),
+                Err(Error::TaskNotFound)
+            );
+            assert_eq!(
+                tasks.relocate_task(accounts.bob, accounts.charlie, moved.id),
+                Ok(())
+            );
+
+            assert_eq!(tasks.get_task(accounts.bob), vec![]);
+            assert_eq!(tasks.get_task(accounts.charlie), vec![moved]);
+        }
+
+        #[ink::test]
+        fn moves_between_lists_cannot_be_undone() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            for title in ["A", "B"] {
+                tasks
+                    .add_task(accounts.bob, task(title, TaskState::Todo))
+                    .unwrap();
+            }
+            tasks
+                .add_task(accounts.charlie, task("C", TaskState::Todo))
+                .unwrap();
+            let id = tasks.get_task(accounts.bob)[1].id;
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            tasks
+                .delegate_task("A".to_string(), accounts.charlie)
+                .unwrap();
+            assert_eq!(tasks.undo(accounts.bob), Err(Error::NothingToUndo));
+            assert_eq!(tasks.undo(accounts.charlie), Err(Error::NothingToUndo));
+
+            tasks
+                .relocate_task(accounts.bob, accounts.charlie, id)
+                .unwrap();
+            assert_eq!(tasks.undo(accounts.bob), Err(Error::NothingToUndo));
+            assert_eq!(tasks.undo(accounts.charlie), Err(Error::NothingToUndo));
+            assert_eq!(tasks.get_task(accounts.bob), vec![]);
+            assert_eq!(
+                titles(&tasks.get_task(accounts.charlie)),
+                vec!["C", "A", "B"]
+            );
+        }
+
+        #[ink::test]
+        fn relocate_task_rejects_unauthorized_caller() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            tasks
+                .add_task(accounts.bob, task("A", TaskState::Todo))
+                .unwrap();
+            let id = tasks.get_task(accounts.bob)[0].id;
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(
+                tasks.relocate_task(accounts.bob, accounts.charlie, id),
+                Err(Error::NotAuthorized)
+            );
+            assert_eq!(titles(&tasks.get_task(accounts.bob)), vec!["A"]);
+        }
+
+        #[ink::test]
+        fn relocate_task_respects_destination_limits() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            let big = Task {
+                description: "a".repeat(200),
+                ..task("Big", TaskState::Todo)
+            };
+            tasks
+                .add_task(accounts.bob, task("A", TaskState::Wip))
+                .unwrap();
+            tasks.add_task(accounts.bob, big).unwrap();
+            tasks
+                .add_task(accounts.charlie, task("Call", TaskState::Wip))
+                .unwrap();
+            let ids: Vec<u64> = tasks.get_task(accounts.bob).iter().map(|t| t.id).collect();
+
+            assert_eq!(tasks.set_max_wip(1), Ok(()));
+            assert_eq!(
+                tasks.relocate_task(accounts.bob, accounts.charlie, ids[0]),
+                Err(Error::WipLimitReached)
+            );
+
+            let quota = scale::Encode::encoded_size(&tasks.get_task(accounts.bob));
+            assert_eq!(tasks.set_max_bytes_per_user(quota as u32), Ok(()));
+            assert_eq!(
+                tasks.relocate_task(accounts.bob, accounts.charlie, ids[1]),
+                Err(Error::QuotaExceeded)
+            );
+            assert_eq!(titles(&tasks.get_task(accounts.bob)), vec!["A", "Big"]);
+            assert_eq!(titles(&tasks.get_task(accounts.charlie)), vec!["Call"]);
+        }
+
+        #[ink::test]
+        fn compact_list_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Done)).unwrap();
+            tasks.add_task(alice, task("B", TaskState::Todo)).unwrap();
+            tasks.add_task(alice, task("C", TaskState::Wip)).unwrap();
+
+            assert_eq!(
+                tasks.compact_list(alice),
+                vec![
+                    ("A".to_string(), 2),
+                    ("B".to_string(), 0),
+                    ("C".to_string(), 1)
+                ]
+            );
+        }
+
+        #[ink::test]
+        fn fire_reminders_skips_tasks_not_overdue() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(500);
+            tasks
+                .add_task(
+                    accounts.alice,
+                    Task {
+                        due: Some(100),
+                        ..task("A", TaskState::Done)
+                    },
+                )
+                .unwrap();
+            tasks
+                .add_task(
+                    accounts.alice,
+                    Task {
+                        due: Some(1_000),
+                        ..task("B", TaskState::Todo)
+                    },
+                )
+                .unwrap();
+            tasks
+                .add_task(accounts.alice, task("C", TaskState::Wip))
+                .unwrap();
+            assert_eq!(tasks.fire_reminders(accounts.alice), 0);
+
+            tasks.set_reminder_contract(accounts.django);
+            assert_eq!(tasks.fire_reminders(accounts.alice), 0);
+        }
+
+        /// The off-chain environment cannot run cross-contract calls, so reaching
+        /// the call for an overdue task panics.
+        #[ink::test]
+        #[should_panic(expected = "off-chain environment does not support contract invocation")]
+        fn fire_reminders_calls_for_overdue_tasks() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            tasks.set_reminder_contract(accounts.django);
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(500);
+            tasks
+                .add_task(
+                    accounts.alice,
+                    Task {
+                        due: Some(100),
+                        ..task("A", TaskState::Todo)
+                    },
+                )
+                .unwrap();
+
+            tasks.fire_reminders(accounts.alice);
+        }
+
+        #[ink::test]
+        fn cleanup_empty_entries_works() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            for user in [accounts.bob, accounts.alice, accounts.charlie] {
+                tasks.add_task(user, task("A", TaskState::Todo)).unwrap();
+            }
+            tasks.remove_task(accounts.bob, "A".to_string()).unwrap();
+            tasks
+                .remove_task(accounts.charlie, "A".to_string())
+                .unwrap();
+            assert_eq!(tasks.get_all_users().len(), 3);
+
+            assert_eq!(tasks.cleanup_empty_entries(), Ok(2));
+            assert_eq!(tasks.get_all_users(), vec![accounts.alice]);
+            assert!(!tasks.tasks.contains(accounts.bob));
+            assert_eq!(tasks.cleanup_empty_entries(), Ok(0));
+
+            tasks
+                .add_task(accounts.django, task("A", TaskState::Todo))
+                .unwrap();
+            assert_eq!(tasks.get_all_users(), vec![accounts.alice, accounts.django]);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(tasks.cleanup_empty_entries(), Err(Error::NotOwner));
+        }
+
+        #[ink::test]
+        fn remaining_effort_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            let effort = |title, state, effort_minutes| Task {
+                effort_minutes,
+                ..task(title, state)
+            };
+            tasks
+                .add_task(alice, effort("A", TaskState::Todo, 30))
+                .unwrap();
+            tasks
+                .add_task(alice, effort("B", TaskState::Wip, 45))
+                .unwrap();
+            tasks
+                .add_task(alice, effort("C", TaskState::Done, 60))
+                .unwrap();
+            assert_eq!(tasks.remaining_effort(alice), 75);
+
+            tasks
+                .update_task(alice, "B".to_string(), None, Some(TaskState::Done))
+                .unwrap();
+            assert_eq!(tasks.remaining_effort(alice), 30);
+
+            tasks
+                .update_task(alice, "A".to_string(), None, Some(TaskState::Done))
+                .unwrap();
+            assert_eq!(tasks.remaining_effort(alice), 0);
+        }
+
+        #[ink::test]
+        fn record_burndown_works() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            tasks
+                .add_task(accounts.alice, task("A", TaskState::Todo))
+                .unwrap();
+            tasks
+                .add_task(accounts.alice, task("B", TaskState::Wip))
+                .unwrap();
+            tasks
+                .add_task(accounts.alice, task("C", TaskState::Done))
+                .unwrap();
+            assert_eq!(tasks.open_task_count(accounts.alice), 2);
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(100);
+            assert_eq!(tasks.record_burndown(accounts.alice), Ok(()));
+            tasks
+                .update_task(accounts.alice, "A".to_string(), None, Some(TaskState::Done))
+                .unwrap();
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(200);
+            assert_eq!(tasks.record_burndown(accounts.alice), Ok(()));
+
+            assert_eq!(tasks.get_burndown(accounts.alice), vec![(100, 2), (200, 1)]);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(tasks.record_burndown(accounts.alice), Err(Error::NotOwner));
+        }
+
+        #[ink::test]
+        fn burndown_history_is_capped() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            for now in 0..=MAX_BURNDOWN_POINTS as u64 {
+                ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(now);
+                tasks.record_burndown(alice).unwrap();
+            }
+
+            let burndown = tasks.get_burndown(alice);
+            assert_eq!(burndown.len(), MAX_BURNDOWN_POINTS);
+            assert_eq!(burndown[0], (1, 0));
+        }
+
+        #[ink::test]
+        fn reorder_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            for title in ["A", "B", "C"] {
+                tasks.add_task(alice, task(title, TaskState::Todo)).unwrap();
+            }
+            let order = |titles: &[&str]| titles.iter().map(|t| t.to_string()).collect();
+
+            assert_eq!(tasks.reorder(alice, order(&["C", "A", "B"])), Ok(()));
+            assert_eq!(titles(&tasks.get_task(alice)), vec!["C", "A", "B"]);
+        }
+
+        #[ink::test]
+        fn reorder_rejects_non_permutations() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            for title in ["A", "B", "C"] {
+                tasks.add_task(alice, task(title, TaskState::Todo)).unwrap();
+            }
+            let order = |titles: &[&str]| titles.iter().map(|t| t.to_string()).collect();
+
+            assert_eq!(
+                tasks.reorder(alice, order(&["C", "A"])),
+                Err(Error::InvalidOrdering)
+            );
+            assert_eq!(
+                tasks.reorder(alice, order(&["C", "A", "B", "D"])),
+                Err(Error::InvalidOrdering)
+            );
+            assert_eq!(
+                tasks.reorder(alice, order(&["C", "A", "A"])),
+                Err(Error::InvalidOrdering)
+            );
+            assert_eq!(titles(&tasks.get_task(alice)), vec!["A", "B", "C"]);
+        }
+
+        #[ink::test]
+        fn next_deadline_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            let due = |title, state, due| Task {
+                due: Some(due),
+                ..task(title, state)
+            };
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+            tasks
+                .add_task(alice, due("B", TaskState::Done, 50))
+                .unwrap();
+            assert_eq!(tasks.next_deadline(alice), None);
+
+            tasks
+                .add_task(alice, due("C", TaskState::Todo, 300))
+                .unwrap();
+            tasks
+                .add_task(alice, due("D", TaskState::Wip, 200))
+                .unwrap();
+            tasks
+                .add_task(alice, due("E", TaskState::Todo, 400))
+                .unwrap();
+            assert_eq!(tasks.next_deadline(alice), Some(("D".to_string(), 200)));
+        }
+
+        #[ink::test]
+        fn clone_list_from_works() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            tasks
+                .add_task(accounts.bob, task("A", TaskState::Done))
+                .unwrap();
+            tasks
+                .add_task(accounts.bob, task("B", TaskState::Wip))
+                .unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            tasks.set_list_public(true);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(tasks.clone_list_from(accounts.bob), Ok(2));
+
+            let cloned = tasks.get_task(accounts.charlie);
+            assert_eq!(titles(&cloned), vec!["A", "B"]);
+            assert!(cloned.iter().all(|t| t.state == TaskState::Todo));
+            assert!(cloned.iter().all(|t| t.completed_at.is_none()));
+            assert_eq!(tasks.get_task(accounts.bob)[0].state, TaskState::Done);
+        }
+
+        #[ink::test]
+        fn clone_list_from_rejects_private_lists() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            tasks
+                .add_task(accounts.bob, task("A", TaskState::Todo))
+                .unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(
+                tasks.clone_list_from(accounts.bob),
+                Err(Error::NotAuthorized)
+            );
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            tasks.set_list_public(true);
+            tasks.set_list_public(false);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(
+                tasks.clone_list_from(accounts.bob),
+                Err(Error::NotAuthorized)
+            );
+            assert_eq!(tasks.get_task(accounts.charlie), vec![]);
+        }
+
+        #[ink::test]
+        fn get_public_tasks_works() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            tasks
+                .add_task(accounts.bob, task("A", TaskState::Todo))
+                .unwrap();
+            tasks
+                .add_task(accounts.charlie, task("B", TaskState::Todo))
+                .unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            tasks.set_list_public(true);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.django);
+            assert_eq!(tasks.get_public_tasks(accounts.bob).map(|t| t.len()), Ok(1));
+            assert_eq!(
+                tasks.get_public_tasks(accounts.charlie),
+                Err(Error::NotAuthorized)
+            );
+        }
+
+        #[ink::test]
+        fn count_by_priority_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            assert_eq!(tasks.count_by_priority(alice), (0, 0, 0));
+
+            for (title, priority) in [
+                ("A", Priority::High),
+                ("B", Priority::Low),
+                ("C", Priority::High),
+                ("D", Priority::Medium),
+                ("E", Priority::High),
+            ] {
+                tasks
+                    .add_task(
+                        alice,
+                        Task {
+                            priority,
+                            ..task(title, TaskState::Todo)
+                        },
+                    )
+                    .unwrap();
+            }
+            assert_eq!(tasks.count_by_priority(alice), (3, 1, 1));
+        }
+
+        #[ink::test]
+        fn set_priority_for_state_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Wip)).unwrap();
+            tasks.add_task(alice, task("B", TaskState::Todo)).unwrap();
+            tasks
+                .add_task(
+                    alice,
+                    Task {
+                        priority: Priority::High,
+                        ..task("C", TaskState::Wip)
+                    },
+                )
+                .unwrap();
+            tasks.add_task(alice, task("D", TaskState::Wip)).unwrap();
+
+            assert_eq!(
+                tasks.set_priority_for_state(alice, TaskState::Wip, Priority::High),
+                Ok(2)
+            );
+            let priorities: Vec<Priority> =
+                tasks.get_task(alice).iter().map(|t| t.priority).collect();
+            assert_eq!(
+                priorities,
+                vec![
+                    Priority::High,
+                    Priority::Medium,
+                    Priority::High,
+                    Priority::High
+                ]
+            );
+            assert_eq!(
+                tasks.set_priority_for_state(alice, TaskState::Wip, Priority::High),
+                Ok(0)
+            );
+        }
+
+        #[ink::test]
+        fn get_task_strict_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            assert_eq!(tasks.get_task_strict(alice), Err(Error::UserNotFound));
+
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+            assert_eq!(tasks.get_task_strict(alice).map(|t| t.len()), Ok(1));
+
+            tasks.remove_task(alice, "A".to_string()).unwrap();
+            assert_eq!(tasks.get_task_strict(alice), Ok(vec![]));
+        }
+
+        #[ink::test]
+        fn start_next_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            assert_eq!(tasks.start_next(alice), None);
+
+            tasks.add_task(alice, task("A", TaskState::Done)).unwrap();
+            tasks.add_task(alice, task("B", TaskState::Todo)).unwrap();
+            tasks.add_task(alice, task("C", TaskState::Todo)).unwrap();
+
+            let started = tasks.start_next(alice).unwrap();
+            assert_eq!(started.title, "B");
+            assert_eq!(started.state, TaskState::Wip);
+            assert_eq!(tasks.get_task(alice)[1], started);
+        }
+
+        #[ink::test]
+        fn start_next_respects_wip_limit() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            assert_eq!(tasks.set_max_wip(1), Ok(()));
+            tasks.add_task(alice, task("A", TaskState::Wip)).unwrap();
+            tasks.add_task(alice, task("B", TaskState::Todo)).unwrap();
 
-    /// Represents the state of a task
-    #[derive(scale::Decode, scale::Encode, PartialEq, Eq)]
-    #[cfg_attr(
-        feature = "std",
-        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
-    )]
-    pub enum TaskState {
-        Todo,
-        Wip,
-        Done,
-    }
+            assert_eq!(tasks.start_next(alice), None);
+            assert_eq!(tasks.get_task(alice)[1].state, TaskState::Todo);
+        }
 
-    /// A single task
-    #[derive(scale::Decode, scale::Encode, PartialEq, Eq)]
-    #[cfg_attr(
-        feature = "std",
-        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
-    )]
-    pub struct Task {
-        title: String,
-        description: String,
-        state: TaskState,
-    }
+        #[ink::test]
+        fn stale_wip_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(100);
+            tasks.add_task(alice, task("A", TaskState::Wip)).unwrap();
+            tasks.add_task(alice, task("B", TaskState::Todo)).unwrap();
+            tasks.add_task(alice, task("C", TaskState::Todo)).unwrap();
 
-    /// Task storage
-    #[ink(storage)]
-    pub struct Tasks {
-        tasks: Mapping<AccountId, Vec<Task>>,
-    }
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1_000);
+            tasks
+                .update_task(alice, "B".to_string(), None, Some(TaskState::Wip))
+                .unwrap();
 
-    impl Tasks {
-        #[ink(constructor)]
-        pub fn new() -> Self {
-            Self::default()
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1_500);
+            assert_eq!(titles(&tasks.stale_wip(alice, 1_000)), vec!["A"]);
+            assert_eq!(titles(&tasks.stale_wip(alice, 100)), vec!["A", "B"]);
+            assert_eq!(tasks.stale_wip(alice, 2_000), vec![]);
         }
 
-        #[ink(constructor)]
-        pub fn default() -> Self {
-            Self {
-                tasks: Mapping::new(),
-            }
+        #[ink::test]
+        fn task_edits_refresh_updated_at() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            let updated_at = |tasks: &Tasks| tasks.get_task(alice)[0].updated_at;
+            let set_now = ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>;
+            set_now(100);
+            tasks.add_task(alice, task("A", TaskState::Wip)).unwrap();
+
+            set_now(200);
+            tasks
+                .add_tag(alice, "A".to_string(), "work".to_string())
+                .unwrap();
+            assert_eq!(updated_at(&tasks), 200);
+
+            set_now(300);
+            tasks
+                .toggle_tag(alice, "A".to_string(), "home".to_string())
+                .unwrap();
+            assert_eq!(updated_at(&tasks), 300);
+
+            set_now(400);
+            tasks
+                .remove_tag(alice, "A".to_string(), "work".to_string())
+                .unwrap();
+            assert_eq!(updated_at(&tasks), 400);
+
+            set_now(500);
+            tasks
+                .tag_tasks(alice, vec!["A".to_string()], "work".to_string())
+                .unwrap();
+            assert_eq!(updated_at(&tasks), 500);
+
+            set_now(600);
+            tasks
+                .rename_task(alice, "A".to_string(), "B".to_string())
+                .unwrap();
+            assert_eq!(updated_at(&tasks), 600);
+
+            set_now(700);
+            tasks
+                .set_priority_for_state(alice, TaskState::Wip, Priority::High)
+                .unwrap();
+            assert_eq!(updated_at(&tasks), 700);
+            assert_eq!(tasks.stale_wip(alice, 50), vec![]);
         }
 
-        /// Add a task to the storage
-        #[ink(message)]
-        pub fn add_task(&mut self, user: AccountId, task: Task) {
-            if !self.tasks.contains(user) {
-                let empty_tasks: Vec<Task> = Vec::new();
-                self.tasks.insert(user, &empty_tasks);
+        #[ink::test]
+        fn upgrade_window_blocks_mutations() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            tasks
+                .add_task(accounts.alice, task("A", TaskState::Todo))
+                .unwrap();
+            tasks
+                .add_task(accounts.bob, task("B", TaskState::Todo))
+                .unwrap();
+            tasks
+                .add_task(accounts.bob, task("C", TaskState::Todo))
+                .unwrap();
+
+            assert_eq!(tasks.prepare_upgrade(), Ok(3));
+            assert_eq!(
+                tasks.add_task(accounts.alice, task("D", TaskState::Todo)),
+                Err(Error::Paused)
+            );
+            assert_eq!(
+                tasks.remove_task(accounts.bob, "B".to_string()),
+                Err(Error::Paused)
+            );
+            assert_eq!(tasks.get_task(accounts.bob).len(), 2);
+
+            assert_eq!(tasks.finish_upgrade(), Ok(()));
+            assert_eq!(
+                tasks.add_task(accounts.alice, task("D", TaskState::Todo)),
+                Ok(())
+            );
+        }
+
+        #[ink::test]
+        fn upgrade_window_is_owner_only() {
+            let mut tasks = Tasks::new();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts().bob);
+            assert_eq!(tasks.prepare_upgrade(), Err(Error::NotOwner));
+            assert_eq!(tasks.finish_upgrade(), Err(Error::NotOwner));
+        }
+
+        #[ink::test]
+        fn incomplete_metadata_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks
+                .add_task(
+                    alice,
+                    Task {
+                        description: "Milk and eggs".to_string(),
+                        due: Some(100),
+                        ..task("A", TaskState::Todo)
+                    },
+                )
+                .unwrap();
+            tasks
+                .add_task(
+                    alice,
+                    Task {
+                        due: Some(100),
+                        ..task("B", TaskState::Todo)
+                    },
+                )
+                .unwrap();
+
+            assert_eq!(tasks.incomplete_metadata(alice), vec!["B".to_string()]);
+        }
+
+        #[ink::test]
+        fn merge_tasks_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            let described = |title, description: &str| Task {
+                description: description.to_string(),
+                ..task(title, TaskState::Todo)
+            };
+            tasks.add_task(alice, described("A", "Buy milk")).unwrap();
+            tasks.add_task(alice, described("B", "Buy eggs")).unwrap();
+            tasks.add_task(alice, described("C", "")).unwrap();
+            tasks
+                .add_tag(alice, "B".to_string(), "shop".to_string())
+                .unwrap();
+
+            assert_eq!(
+                tasks.merge_tasks(alice, "C".to_string(), "B".to_string()),
+                Ok(())
+            );
+            assert_eq!(
+                tasks.merge_tasks(alice, "A".to_string(), "C".to_string()),
+                Ok(())
+            );
+
+            let list = tasks.get_task(alice);
+            assert_eq!(titles(&list), vec!["A"]);
+            assert_eq!(list[0].description, "Buy milk\nBuy eggs");
+            assert_eq!(list[0].tags, vec!["shop".to_string()]);
+        }
+
+        #[ink::test]
+        fn merge_tasks_rejects_unknown_titles() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+
+            assert_eq!(
+                tasks.merge_tasks(alice, "A".to_string(), "B".to_string()),
+                Err(Error::TaskNotFound)
+            );
+            assert_eq!(
+                tasks.merge_tasks(alice, "B".to_string(), "A".to_string()),
+                Err(Error::TaskNotFound)
+            );
+            assert_eq!(
+                tasks.merge_tasks(alice, "A".to_string(), "A".to_string()),
+                Err(Error::TaskNotFound)
+            );
+            assert_eq!(titles(&tasks.get_task(alice)), vec!["A"]);
+        }
+
+        #[ink::test]
+        fn merge_tasks_respects_the_tag_cap() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            let tagged = |title, prefix: &str| Task {
+                tags: (0..MAX_TAGS_PER_TASK)
+                    .map(|i| format!("{prefix}{i}"))
+                    .collect(),
+                ..task(title, TaskState::Todo)
+            };
+            tasks.add_task(alice, tagged("A", "a")).unwrap();
+            tasks.add_task(alice, tagged("B", "b")).unwrap();
+
+            assert_eq!(
+                tasks.merge_tasks(alice, "A".to_string(), "B".to_string()),
+                Err(Error::TagLimitReached)
+            );
+            assert_eq!(titles(&tasks.get_task(alice)), vec!["A", "B"]);
+            assert_eq!(tasks.get_task(alice)[0].tags.len(), MAX_TAGS_PER_TASK);
+        }
+
+        #[ink::test]
+        fn count_distribution_works() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            tasks
+                .add_task(accounts.alice, task("A", TaskState::Todo))
+                .unwrap();
+            tasks.remove_task(accounts.alice, "A".to_string()).unwrap();
+            for (user, count) in [
+                (accounts.bob, 2),
+                (accounts.charlie, 5),
+                (accounts.django, 2),
+            ] {
+                for title in ["A", "B", "C", "D", "E"].into_iter().take(count) {
+                    tasks.add_task(user, task(title, TaskState::Todo)).unwrap();
+                }
             }
 
-            let mut user_task = self.tasks.get(user).unwrap();
-            user_task.push(task);
-            self.tasks.insert(user, &user_task);
+            assert_eq!(tasks.count_distribution(), Ok(vec![(0, 1), (2, 2), (5, 1)]));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(tasks.count_distribution(), Err(Error::NotOwner));
         }
 
-        /// Remove a task from the storage
-        #[ink(message)]
-        pub fn remove_task(&mut self, user: AccountId, task_title: String) {
-            if !self.tasks.contains(user) {
-                let empty_tasks: Vec<Task> = Vec::new();
-                self.tasks.insert(user, &empty_tasks);
-            } else if !self.tasks.get(user).unwrap().is_empty() {
-                let mut tasks = self.tasks.get(user).unwrap();
-                tasks.remove(
-                    tasks
-                        .iter()
-                        .position(|t| t.title == task_title)
-                        .unwrap_or_default(),
+        #[ink::test]
+        fn add_task_checked_warns_past_soft_limit() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            assert_eq!(
+                tasks.add_task_checked(alice, task("A", TaskState::Todo)),
+                Ok((1, false, 0))
+            );
+
+            assert_eq!(tasks.set_soft_task_limit(2), Ok(()));
+            assert_eq!(
+                tasks.add_task_checked(alice, task("B", TaskState::Todo)),
+                Ok((2, false, 0))
+            );
+            assert_eq!(
+                tasks.add_task_checked(alice, task("C", TaskState::Todo)),
+                Ok((3, true, 0))
+            );
+            assert_eq!(tasks.get_task(alice).len(), 3);
+        }
+
+        #[ink::test]
+        fn all_done_works() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            assert!(!tasks.all_done(accounts.alice));
+
+            tasks
+                .add_task(accounts.alice, task("A", TaskState::Done))
+                .unwrap();
+            tasks
+                .add_task(accounts.alice, task("B", TaskState::Done))
+                .unwrap();
+            assert!(tasks.all_done(accounts.alice));
+
+            tasks
+                .add_task(accounts.bob, task("A", TaskState::Done))
+                .unwrap();
+            tasks
+                .add_task(accounts.bob, task("B", TaskState::Wip))
+                .unwrap();
+            assert!(!tasks.all_done(accounts.bob));
+        }
+
+        #[ink::test]
+        fn reset_all_to_todo_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Done)).unwrap();
+            tasks.add_task(alice, task("B", TaskState::Todo)).unwrap();
+            tasks.add_task(alice, task("C", TaskState::Wip)).unwrap();
+
+            assert_eq!(tasks.reset_all_to_todo(alice), Ok(2));
+            let list = tasks.get_task(alice);
+            assert!(list.iter().all(|t| t.state == TaskState::Todo));
+            assert!(list.iter().all(|t| t.completed_at.is_none()));
+            assert_eq!(tasks.reset_all_to_todo(alice), Ok(0));
+        }
+
+        #[ink::test]
+        fn tags_per_task_are_capped() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+            tasks.add_task(alice, task("B", TaskState::Todo)).unwrap();
+            for i in 0..MAX_TAGS_PER_TASK {
+                assert_eq!(
+                    tasks.add_tag(alice, "A".to_string(), format!("tag{i}")),
+                    Ok(())
                 );
-                self.tasks.insert(user, &tasks);
             }
+
+            assert_eq!(
+                tasks.add_tag(alice, "A".to_string(), "extra".to_string()),
+                Err(Error::TagLimitReached)
+            );
+            assert_eq!(
+                tasks.add_tag(alice, "A".to_string(), "tag0".to_string()),
+                Ok(())
+            );
+            assert_eq!(
+                tasks.tag_tasks(
+                    alice,
+                    vec!["B".to_string(), "A".to_string()],
+                    "extra".to_string()
+                ),
+                Err(Error::TagLimitReached)
+            );
+            assert_eq!(tasks.get_task(alice)[0].tags.len(), MAX_TAGS_PER_TASK);
         }
 
-        /// Fetchs the task of a single user
-        #[ink(message)]
-        pub fn get_task(&self, user: AccountId) -> Vec<Task> {
-            if !self.tasks.contains(user) {
-                let empty_tasks: Vec<Task> = Vec::new();
-                empty_tasks
-            } else {
-                self.tasks.get(user).unwrap()
+        #[ink::test]
+        fn promote_subtask_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks
+                .add_task(
+                    alice,
+                    Task {
+                        subtasks: vec!["Milk".to_string(), "Eggs".to_string()],
+                        ..task("Groceries", TaskState::Wip)
+                    },
+                )
+                .unwrap();
+
+            assert_eq!(
+                tasks.promote_subtask(alice, "Groceries".to_string(), "Eggs".to_string()),
+                Ok(())
+            );
+
+            let list = tasks.get_task(alice);
+            assert_eq!(titles(&list), vec!["Groceries", "Eggs"]);
+            assert_eq!(list[0].subtasks, vec!["Milk".to_string()]);
+            assert_eq!(list[1].state, TaskState::Todo);
+
+            assert_eq!(tasks.undo(alice), Ok(()));
+            let list = tasks.get_task(alice);
+            assert_eq!(titles(&list), vec!["Groceries"]);
+            assert_eq!(
+                list[0].subtasks,
+                vec!["Milk".to_string(), "Eggs".to_string()]
+            );
+        }
+
+        #[ink::test]
+        fn promote_subtask_rejects_missing_parent_or_item() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks
+                .add_task(
+                    alice,
+                    Task {
+                        subtasks: vec!["Milk".to_string()],
+                        ..task("Groceries", TaskState::Todo)
+                    },
+                )
+                .unwrap();
+
+            assert_eq!(
+                tasks.promote_subtask(alice, "Chores".to_string(), "Milk".to_string()),
+                Err(Error::TaskNotFound)
+            );
+            assert_eq!(
+                tasks.promote_subtask(alice, "Groceries".to_string(), "Eggs".to_string()),
+                Err(Error::TaskNotFound)
+            );
+            assert_eq!(tasks.get_task(alice).len(), 1);
+        }
+
+        #[ink::test]
+        fn oldest_open_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            assert_eq!(tasks.oldest_open(alice), None);
+
+            for (title, now) in [("B", 200), ("A", 100), ("C", 300)] {
+                ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(now);
+                tasks.add_task(alice, task(title, TaskState::Todo)).unwrap();
             }
+            assert_eq!(
+                tasks.oldest_open(alice).map(|t| t.title),
+                Some("A".to_string())
+            );
+
+            tasks
+                .update_task(alice, "A".to_string(), None, Some(TaskState::Done))
+                .unwrap();
+            assert_eq!(
+                tasks.oldest_open(alice).map(|t| t.title),
+                Some("B".to_string())
+            );
         }
-    }
 
-    impl Default for Tasks {
-        fn default() -> Self {
-            Self::new()
+        #[ink::test]
+        fn schedule_all_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+            tasks
+                .add_task(
+                    alice,
+                    Task {
+                        due: Some(50),
+                        ..task("B", TaskState::Todo)
+                    },
+                )
+                .unwrap();
+            tasks.add_task(alice, task("C", TaskState::Done)).unwrap();
+            tasks.add_task(alice, task("D", TaskState::Wip)).unwrap();
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1_000);
+            assert_eq!(tasks.schedule_all(alice, DAY_MS), Ok(2));
+
+            let due: Vec<Option<u64>> = tasks.get_task(alice).iter().map(|t| t.due).collect();
+            assert_eq!(
+                due,
+                vec![Some(1_000 + DAY_MS), Some(50), None, Some(1_000 + DAY_MS)]
+            );
+            assert_eq!(tasks.schedule_all(alice, DAY_MS), Ok(0));
+        }
+
+        #[ink::test]
+        fn user_settings_work() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            let settings = UserSettings {
+                wip_limit: Some(1),
+                soft_task_limit: Some(1),
+                max_bytes: None,
+            };
+            assert_eq!(tasks.set_user_settings(accounts.alice, settings), Ok(()));
+
+            assert_eq!(tasks.get_user_settings(accounts.alice), settings);
+            assert_eq!(
+                tasks.get_user_settings(accounts.bob),
+                UserSettings::default()
+            );
+
+            for user in [accounts.alice, accounts.bob] {
+                tasks.add_task(user, task("A", TaskState::Wip)).unwrap();
+            }
+            assert_eq!(
+                tasks.add_task_checked(accounts.alice, task("B", TaskState::Wip)),
+                Err(Error::WipLimitReached)
+            );
+            assert_eq!(
+                tasks.add_task_checked(accounts.alice, task("B", TaskState::Todo)),
+                Ok((2, true, 0))
+            );
+            assert_eq!(
+                tasks.add_task_checked(accounts.bob, task("B", TaskState::Wip)),
+                Ok((2, false, 0))
+            );
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                tasks.set_user_settings(accounts.bob, settings),
+                Err(Error::NotOwner)
+            );
+        }
+
+        #[ink::test]
+        fn lifetime_stats_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            assert_eq!(tasks.lifetime_stats(alice), (0, 0));
+
+            for title in ["A", "B", "C"] {
+                tasks.add_task(alice, task(title, TaskState::Todo)).unwrap();
+            }
+            tasks.remove_task(alice, "B".to_string()).unwrap();
+            assert_eq!(tasks.lifetime_stats(alice), (3, 1));
+            assert_eq!(tasks.get_task(alice).len(), 2);
+
+            assert_eq!(
+                tasks.remove_task(alice, "B".to_string()),
+                Err(Error::TaskNotFound)
+            );
+            assert_eq!(tasks.lifetime_stats(alice), (3, 1));
+            assert_eq!(titles(&tasks.get_task(alice)), vec!["A", "C"]);
+
+            tasks
+                .merge_tasks(alice, "A".to_string(), "C".to_string())
+                .unwrap();
+            assert_eq!(tasks.lifetime_stats(alice), (3, 2));
+        }
+
+        #[ink::test]
+        fn lifetime_stats_follow_every_write() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            let (alice, bob) = (accounts.alice, accounts.bob);
+            assert_eq!(
+                tasks.remove_task(bob, "A".to_string()),
+                Err(Error::TaskNotFound)
+            );
+            assert_eq!(tasks.get_all_users(), vec![]);
+
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+            tasks
+                .replace_all_tasks(
+                    alice,
+                    vec![task("X", TaskState::Todo), task("Y", TaskState::Todo)],
+                )
+                .unwrap();
+            assert_eq!(tasks.lifetime_stats(alice), (3, 1));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(alice);
+            tasks.delegate_task("X".to_string(), bob).unwrap();
+            assert_eq!(tasks.lifetime_stats(alice), (3, 2));
+            assert_eq!(tasks.lifetime_stats(bob), (1, 0));
+
+            let id = tasks.get_task(bob)[0].id;
+            tasks.relocate_task(bob, accounts.charlie, id).unwrap();
+            assert_eq!(tasks.lifetime_stats(bob), (1, 1));
+            assert_eq!(tasks.lifetime_stats(accounts.charlie), (1, 0));
+
+            tasks
+                .update_task(alice, "Y".to_string(), None, Some(TaskState::Done))
+                .unwrap();
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(10);
+            assert_eq!(tasks.auto_archive_done_older_than(alice, 0), Ok(1));
+            assert_eq!(tasks.lifetime_stats(alice), (3, 3));
+        }
+
+        #[ink::test]
+        fn titles_with_prefix_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            for title in ["Buy milk", "Call mom", "Buy eggs"] {
+                tasks.add_task(alice, task(title, TaskState::Todo)).unwrap();
+            }
+            let prefixed =
+                |tasks: &Tasks, prefix: &str| tasks.titles_with_prefix(alice, prefix.to_string());
+
+            assert_eq!(
+                prefixed(&tasks, "Buy"),
+                vec!["Buy milk".to_string(), "Buy eggs".to_string()]
+            );
+            assert_eq!(prefixed(&tasks, "").len(), 3);
+            assert_eq!(prefixed(&tasks, "buy"), Vec::<String>::new());
+        }
+
+        #[ink::test]
+        fn insert_task_at_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("B", TaskState::Todo)).unwrap();
+            tasks.add_task(alice, task("D", TaskState::Todo)).unwrap();
+
+            assert_eq!(
+                tasks.insert_task_at(alice, 0, task("A", TaskState::Todo)),
+                Ok(())
+            );
+            assert_eq!(
+                tasks.insert_task_at(alice, 2, task("C", TaskState::Todo)),
+                Ok(())
+            );
+            assert_eq!(
+                tasks.insert_task_at(alice, 100, task("E", TaskState::Todo)),
+                Ok(())
+            );
+            assert_eq!(
+                titles(&tasks.get_task(alice)),
+                vec!["A", "B", "C", "D", "E"]
+            );
+        }
+
+        #[ink::test]
+        fn insert_task_at_rejects_duplicates() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+
+            assert_eq!(
+                tasks.insert_task_at(alice, 0, task("A", TaskState::Wip)),
+                Err(Error::DuplicateTitle)
+            );
+            assert_eq!(
+                tasks.insert_task_at(alice, 0, task("A\nB", TaskState::Wip)),
+                Err(Error::InvalidTitle)
+            );
+            assert_eq!(tasks.get_task(alice).len(), 1);
+        }
+
+        #[ink::test]
+        fn writes_emit_mutation_applied() {
+            let mut tasks = Tasks::new();
+            let (alice, bob) = (accounts().alice, accounts().bob);
+            let last_mutation = || {
+                let event = ink::env::test::recorded_events().last().unwrap();
+                match <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap() {
+                    Event::MutationApplied(m) => (m.user, m.op, m.new_count),
+                    _ => panic!("expected a MutationApplied event"),
+                }
+            };
+
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+            tasks.add_task(alice, task("B", TaskState::Todo)).unwrap();
+            assert_eq!(ink::env::test::recorded_events().count(), 4);
+            let event = ink::env::test::recorded_events().nth(2).unwrap();
+            match <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap() {
+                Event::MutationApplied(m) => {
+                    assert_eq!((m.user, m.op, m.new_count), (alice, OP_ADD, 2))
+                }
+                _ => panic!("expected a MutationApplied event"),
+            }
+
+            tasks.remove_task(alice, "A".to_string()).unwrap();
+            assert_eq!(last_mutation(), (alice, OP_REMOVE, 1));
+
+            tasks
+                .update_task(alice, "B".to_string(), None, Some(TaskState::Wip))
+                .unwrap();
+            assert_eq!(last_mutation(), (alice, OP_UPDATE, 1));
+
+            let events_before = ink::env::test::recorded_events().count();
+            assert_eq!(
+                tasks.remove_task(bob, "A".to_string()),
+                Err(Error::TaskNotFound)
+            );
+            assert_eq!(ink::env::test::recorded_events().count(), events_before);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(alice);
+            tasks.delegate_task("B".to_string(), bob).unwrap();
+            let events: Vec<_> = ink::env::test::recorded_events().collect();
+            assert_eq!(events.len() - events_before, 3);
+            let mutations: Vec<_> = events[events_before..events_before + 2]
+                .iter()
+                .map(
+                    |e| match <Event as scale::Decode>::decode(&mut &e.data[..]).unwrap() {
+                        Event::MutationApplied(m) => (m.user, m.op, m.new_count),
+                        _ => panic!("expected a MutationApplied event"),
+                    },
+                )
+                .collect();
+            assert_eq!(mutations, vec![(alice, OP_REMOVE, 0), (bob, OP_ADD, 1)]);
+        }
+
+        #[ink::test]
+        fn footprint_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            assert_eq!(tasks.footprint(alice), (0, 1));
+
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+            tasks.add_task(alice, task("B", TaskState::Done)).unwrap();
+            let bytes = tasks.get_task(alice).encoded_size() as u32;
+            assert_eq!(tasks.footprint(alice), (2, bytes));
+        }
+
+        #[ink::test]
+        fn tasks_assigned_count_works() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            let assigned = |title, assignee| Task {
+                assignee: Some(assignee),
+                ..task(title, TaskState::Todo)
+            };
+            tasks
+                .add_task(accounts.alice, assigned("A", accounts.django))
+                .unwrap();
+            tasks
+                .add_task(accounts.alice, assigned("B", accounts.eve))
+                .unwrap();
+            tasks
+                .add_task(accounts.alice, task("C", TaskState::Todo))
+                .unwrap();
+            tasks
+                .add_task(accounts.bob, assigned("A", accounts.django))
+                .unwrap();
+            tasks
+                .add_task(accounts.bob, assigned("B", accounts.django))
+                .unwrap();
+
+            assert_eq!(tasks.tasks_assigned_count(accounts.django), Ok(3));
+            assert_eq!(tasks.tasks_assigned_count(accounts.eve), Ok(1));
+            assert_eq!(tasks.tasks_assigned_count(accounts.frank), Ok(0));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                tasks.tasks_assigned_count(accounts.django),
+                Err(Error::NotOwner)
+            );
+        }
+
+        #[ink::test]
+        fn toggle_tag_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+            let toggle = |tasks: &mut Tasks, title: &str| {
+                tasks.toggle_tag(alice, title.to_string(), "work".to_string())
+            };
+
+            assert_eq!(toggle(&mut tasks, "A"), Ok(true));
+            assert_eq!(tasks.get_task(alice)[0].tags, vec!["work".to_string()]);
+            assert_eq!(toggle(&mut tasks, "A"), Ok(false));
+            assert_eq!(tasks.get_task(alice)[0].tags, Vec::<String>::new());
+            assert_eq!(toggle(&mut tasks, "A"), Ok(true));
+
+            assert_eq!(toggle(&mut tasks, "B"), Err(Error::TaskNotFound));
+        }
+
+        #[ink::test]
+        fn state_percentages_works() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            assert_eq!(tasks.state_percentages(accounts.alice), (0, 0, 0));
+
+            for (title, state) in [
+                ("A", TaskState::Todo),
+                ("B", TaskState::Todo),
+                ("C", TaskState::Wip),
+                ("D", TaskState::Done),
+            ] {
+                tasks.add_task(accounts.alice, task(title, state)).unwrap();
+            }
+            assert_eq!(tasks.state_percentages(accounts.alice), (50, 25, 25));
+
+            for (title, state) in [
+                ("A", TaskState::Todo),
+                ("B", TaskState::Wip),
+                ("C", TaskState::Wip),
+            ] {
+                tasks.add_task(accounts.bob, task(title, state)).unwrap();
+            }
+            assert_eq!(tasks.state_percentages(accounts.bob), (33, 67, 0));
+        }
+
+        #[ink::test]
+        fn long_descriptions_are_rejected_by_default() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            let described = |description: String| Task {
+                description,
+                ..task("A", TaskState::Todo)
+            };
+
+            assert_eq!(
+                tasks.add_task_checked(alice, described("a".repeat(MAX_DESCRIPTION_LEN + 1))),
+                Err(Error::DescriptionTooLong)
+            );
+            assert_eq!(
+                tasks.add_task_checked(alice, described("a".repeat(MAX_DESCRIPTION_LEN))),
+                Ok((1, false, MAX_DESCRIPTION_LEN as u32))
+            );
+        }
+
+        #[ink::test]
+        fn long_descriptions_can_be_truncated() {
+            let mut tasks = Tasks::with_truncated_descriptions(true);
+            let alice = accounts().alice;
+            let described = |title, description: String| Task {
+                description,
+                ..task(title, TaskState::Todo)
+            };
+
+            assert_eq!(
+                tasks.add_task_checked(alice, described("A", "a".repeat(300))),
+                Ok((1, false, MAX_DESCRIPTION_LEN as u32))
+            );
+            let description = format!("a{}", "é".repeat(200));
+            assert_eq!(
+                tasks.add_task_checked(alice, described("B", description)),
+                Ok((2, false, MAX_DESCRIPTION_LEN as u32 - 1))
+            );
+            assert_eq!(
+                tasks.get_task(alice)[0].description,
+                "a".repeat(MAX_DESCRIPTION_LEN)
+            );
+        }
+
+        #[ink::test]
+        fn global_stale_works() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(100);
+            tasks
+                .add_task(accounts.alice, task("A", TaskState::Todo))
+                .unwrap();
+            tasks
+                .add_task(accounts.alice, task("B", TaskState::Done))
+                .unwrap();
+            tasks
+                .add_task(accounts.bob, task("C", TaskState::Wip))
+                .unwrap();
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1_000);
+            tasks
+                .add_task(accounts.alice, task("D", TaskState::Todo))
+                .unwrap();
+            tasks
+                .add_task(accounts.bob, task("E", TaskState::Wip))
+                .unwrap();
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1_500);
+            assert_eq!(
+                tasks.global_stale(1_000),
+                Ok(vec![
+                    (accounts.alice, "A".to_string()),
+                    (accounts.bob, "C".to_string())
+                ])
+            );
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(tasks.global_stale(1_000), Err(Error::NotOwner));
+        }
+
+        #[ink::test]
+        fn events_can_be_turned_off() {
+            let mut tasks = Tasks::with_events(false);
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+            assert_eq!(ink::env::test::recorded_events().count(), 0);
+        }
+
+        #[ink::test]
+        fn events_are_on_by_default() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+            assert!(ink::env::test::recorded_events().count() > 0);
+        }
+
+        #[ink::test]
+        fn my_role_works() {
+            let mut tasks = Tasks::new();
+            let accounts = accounts();
+            assert_eq!(tasks.set_manager(accounts.bob, true), Ok(()));
+            assert_eq!(tasks.my_role(), 2);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(tasks.my_role(), 1);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(tasks.my_role(), 0);
+        }
+
+        #[ink::test]
+        fn prune_older_than_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            for (title, now) in [("A", 100), ("B", 200), ("C", 300), ("D", 400)] {
+                ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(now);
+                tasks.add_task(alice, task(title, TaskState::Todo)).unwrap();
+            }
+
+            assert_eq!(tasks.prune_older_than(alice, 300), Ok(2));
+            assert_eq!(titles(&tasks.get_task(alice)), vec!["C", "D"]);
+            assert_eq!(tasks.lifetime_stats(alice), (4, 2));
+            assert_eq!(tasks.prune_older_than(alice, 300), Ok(0));
+        }
+
+        #[ink::test]
+        fn title_available_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+            tasks.add_task(alice, task("B", TaskState::Todo)).unwrap();
+            tasks
+                .update_task(alice, "B".to_string(), None, Some(TaskState::Done))
+                .unwrap();
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(DAY_MS);
+            assert_eq!(tasks.auto_archive_done_older_than(alice, 0), Ok(1));
+
+            assert!(tasks.title_available(alice, "C".to_string()));
+            assert!(!tasks.title_available(alice, "A".to_string()));
+            assert!(!tasks.title_available(alice, "B".to_string()));
+            assert!(!tasks.task_exists(alice, "B".to_string()));
+        }
+
+        #[ink::test]
+        fn sort_by_due_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            for (title, due) in [
+                ("A", None),
+                ("B", Some(300)),
+                ("C", None),
+                ("D", Some(100)),
+                ("E", Some(200)),
+            ] {
+                tasks
+                    .add_task(
+                        alice,
+                        Task {
+                            due,
+                            ..task(title, TaskState::Todo)
+                        },
+                    )
+                    .unwrap();
+            }
+
+            tasks.sort_by_due(alice).unwrap();
+            assert_eq!(
+                titles(&tasks.get_task(alice)),
+                vec!["D", "E", "B", "A", "C"]
+            );
+        }
+
+        #[ink::test]
+        fn peek_next_id_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+
+            let next_id = tasks.peek_next_id();
+            tasks.add_task(alice, task("B", TaskState::Todo)).unwrap();
+            assert_eq!(tasks.get_task(alice)[1].id, next_id);
+            assert_eq!(tasks.peek_next_id(), next_id + 1);
+        }
+
+        #[ink::test]
+        fn complete_ids_works() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+            tasks.add_task(alice, task("B", TaskState::Wip)).unwrap();
+            tasks.add_task(alice, task("C", TaskState::Todo)).unwrap();
+            tasks.add_task(alice, task("D", TaskState::Done)).unwrap();
+            let ids: Vec<u64> = tasks.get_task(alice).iter().map(|t| t.id).collect();
+
+            let missing = tasks.peek_next_id();
+            assert_eq!(
+                tasks.complete_ids(alice, vec![ids[0], ids[3], missing]),
+                Ok(1)
+            );
+            assert_eq!(tasks.complete_ids(alice, vec![ids[1]]), Ok(1));
+
+            let states: Vec<TaskState> = tasks.get_task(alice).iter().map(|t| t.state).collect();
+            assert_eq!(
+                states,
+                vec![
+                    TaskState::Done,
+                    TaskState::Done,
+                    TaskState::Todo,
+                    TaskState::Done
+                ]
+            );
+        }
+
+        #[ink::test]
+        fn batch_update_states_respects_wip_limit() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            assert_eq!(tasks.set_max_wip(1), Ok(()));
+            tasks.add_task(alice, task("A", TaskState::Todo)).unwrap();
+            tasks.add_task(alice, task("B", TaskState::Todo)).unwrap();
+
+            let both = vec![
+                ("A".to_string(), TaskState::Wip),
+                ("B".to_string(), TaskState::Wip),
+            ];
+            assert_eq!(
+                tasks.batch_update_states(alice, both),
+                Err(Error::WipLimitReached)
+            );
+            assert_eq!(tasks.count_by_state(alice), (2, 0, 0));
+
+            let swap = vec![("A".to_string(), TaskState::Wip)];
+            assert_eq!(tasks.batch_update_states(alice, swap), Ok(()));
+            let swap = vec![
+                ("A".to_string(), TaskState::Done),
+                ("B".to_string(), TaskState::Wip),
+            ];
+            assert_eq!(tasks.batch_update_states(alice, swap), Ok(()));
+            assert_eq!(tasks.count_by_state(alice), (0, 1, 1));
+        }
+
+        #[ink::test]
+        fn edits_respect_the_description_cap() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            let described = |title, description: String| Task {
+                description,
+                ..task(title, TaskState::Todo)
+            };
+            tasks
+                .add_task(alice, described("A", "a".repeat(200)))
+                .unwrap();
+            tasks
+                .add_task(alice, described("B", "b".repeat(200)))
+                .unwrap();
+
+            let long = "a".repeat(MAX_DESCRIPTION_LEN + 1);
+            assert_eq!(
+                tasks.update_task(alice, "A".to_string(), Some(long), None),
+                Err(Error::DescriptionTooLong)
+            );
+            assert_eq!(
+                tasks.merge_tasks(alice, "A".to_string(), "B".to_string()),
+                Err(Error::DescriptionTooLong)
+            );
+            assert_eq!(titles(&tasks.get_task(alice)), vec!["A", "B"]);
+            assert_eq!(tasks.get_task(alice)[0].description, "a".repeat(200));
+        }
+
+        #[ink::test]
+        fn edits_truncate_long_descriptions() {
+            let mut tasks = Tasks::with_truncated_descriptions(true);
+            let alice = accounts().alice;
+            let described = |title, description: String| Task {
+                description,
+                ..task(title, TaskState::Todo)
+            };
+            tasks
+                .add_task(alice, described("A", "a".repeat(200)))
+                .unwrap();
+            tasks
+                .add_task(alice, described("B", "b".repeat(200)))
+                .unwrap();
+
+            assert_eq!(
+                tasks.merge_tasks(alice, "A".to_string(), "B".to_string()),
+                Ok(())
+            );
+            let list = tasks.get_task(alice);
+            assert_eq!(list[0].description.len(), MAX_DESCRIPTION_LEN);
+            assert!(list[0].description.starts_with(&"a".repeat(200)));
+
+            let long = "c".repeat(300);
+            assert_eq!(
+                tasks.update_task(alice, "A".to_string(), Some(long), None),
+                Ok(())
+            );
+            assert_eq!(
+                tasks.get_task(alice)[0].description,
+                "c".repeat(MAX_DESCRIPTION_LEN)
+            );
+        }
+
+        #[ink::test]
+        fn edits_respect_the_byte_quota() {
+            let mut tasks = Tasks::new();
+            let alice = accounts().alice;
+            tasks
+                .add_task(
+                    alice,
+                    Task {
+                        description: "Buy milk".to_string(),
+                        ..task("A", TaskState::Todo)
+                    },
+                )
+                .unwrap();
+            let quota = scale::Encode::encoded_size(&tasks.get_task(alice));
+            assert_eq!(tasks.set_max_bytes_per_user(quota as u32), Ok(()));
+
+            assert_eq!(
+                tasks.update_task(alice, "A".to_string(), Some("a".repeat(100)), None),
+                Err(Error::QuotaExceeded)
+            );
+            assert_eq!(
+                tasks.rename_task(alice, "A".to_string(), "a".repeat(100)),
+                Err(Error::QuotaExceeded)
+            );
+            assert_eq!(
+                tasks.add_tag(alice, "A".to_string(), "work".to_string()),
+                Err(Error::QuotaExceeded)
+            );
+            assert_eq!(
+                tasks.update_task(alice, "A".to_string(), Some("Milk".to_string()), None),
+                Ok(())
+            );
+            assert_eq!(titles(&tasks.get_task(alice)), vec!["A"]);
         }
     }
 
@@ -158,22 +5680,18 @@ mod tasks {
             let add_task = build_message::<TasksRef>(contract_account_id).call(|tasks| {
                 tasks.add_task(
                     contract_account_id,
-                    Task {
-                        title: "Test".to_string(),
-                        description: "Test".to_string(),
-                        state: TaskState::Todo,
-                    },
+                    Task::new("Test".to_string(), "Test".to_string(), TaskState::Todo),
                 )
             });
             let _add_task_result = client
                 .call(&ink_e2e::bob(), add_task, 0, None)
                 .await
                 .expect("add task failed");
-            _tasks.push(Task {
-                title: "Test".to_string(),
-                description: "Test".to_string(),
-                state: TaskState::Todo,
-            });
+            _tasks.push(Task::new(
+                "Test".to_string(),
+                "Test".to_string(),
+                TaskState::Todo,
+            ));
             // Then
             let get = build_message::<TasksRef>(contract_account_id)
                 .call(|tasks| tasks.get_task(contract_account_id));
@@ -182,5 +5700,40 @@ mod tasks {
 
             Ok(())
         }
+
+        /// We test that other contracts can read progress through the `TaskQuery` trait.
+        #[ink_e2e::test]
+        async fn task_query_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            // Given
+            let constructor = TasksRef::new();
+            let contract_account_id = client
+                .instantiate("tasks", &ink_e2e::charlie(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            let add_task = build_message::<TasksRef>(contract_account_id).call(|tasks| {
+                tasks.add_task(
+                    contract_account_id,
+                    Task::new("Test".to_string(), "Test".to_string(), TaskState::Todo),
+                )
+            });
+            let _add_task_result = client
+                .call(&ink_e2e::charlie(), add_task, 0, None)
+                .await
+                .expect("add task failed");
+
+            // When
+            let task_count = build_message::<TasksRef>(contract_account_id)
+                .call(|tasks| tasks.task_count(contract_account_id));
+            let task_count_result = client
+                .call_dry_run(&ink_e2e::charlie(), &task_count, 0, None)
+                .await;
+
+            // Then
+            assert_eq!(task_count_result.return_value(), 1);
+
+            Ok(())
+        }
     }
 }